@@ -0,0 +1,103 @@
+use std::path::Path;
+
+use sdl2::image::LoadSurface;
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::render::Texture;
+use sdl2::render::TextureCreator;
+use sdl2::surface::Surface;
+use sdl2::sys::SDL_BlendFactor;
+use sdl2::sys::SDL_BlendOperation;
+use sdl2::sys::SDL_ScaleMode;
+
+use crate::config::Filter;
+use crate::error::ApplicationResult;
+
+/// Loads image as texture. With premultiplied alpha color channels of image
+/// are multiplied by alpha and texture uses matching blend mode.
+pub fn load<'b, T, P>(
+    path: P,
+    premultiplied: bool,
+    texture_creator: &'b TextureCreator<T>,
+) -> ApplicationResult<Texture<'b>>
+where
+    P: AsRef<Path>,
+{
+    texture(Surface::from_file(path)?, premultiplied, texture_creator)
+}
+
+/// Creates texture from surface, see `load`.
+pub fn texture<'b, T>(
+    surface: Surface,
+    premultiplied: bool,
+    texture_creator: &'b TextureCreator<T>,
+) -> ApplicationResult<Texture<'b>> {
+    if !premultiplied {
+        return Ok(texture_creator.create_texture_from_surface(&surface)?);
+    }
+
+    let mut surface = surface.convert_format(PixelFormatEnum::RGBA32)?;
+    let width = surface.width() as usize;
+    let height = surface.height() as usize;
+    let pitch = surface.pitch() as usize;
+
+    surface.with_lock_mut(|pixels| {
+        for y in 0..height {
+            for x in 0..width {
+                let offset = y * pitch + 4 * x;
+                let alpha = pixels[offset + 3] as u32;
+
+                for channel in &mut pixels[offset..offset + 3] {
+                    *channel = ((*channel as u32 * alpha + 127) / 255) as u8;
+                }
+            }
+        }
+    });
+
+    let texture = texture_creator.create_texture_from_surface(&surface)?;
+    set_premultiplied(&texture);
+
+    Ok(texture)
+}
+
+/// Sets color and alpha modulation of texture. Premultiplied textures need
+/// color channels scaled by alpha too.
+pub fn set_modulation(texture: &mut Texture, color: u8, alpha: u8, premultiplied: bool) {
+    let color = match premultiplied {
+        true => ((color as u32 * alpha as u32 + 127) / 255) as u8,
+        false => color,
+    };
+
+    texture.set_color_mod(color, color, color);
+    texture.set_alpha_mod(alpha);
+}
+
+/// Sets sampling of scaled texture, overrides renderer scale quality.
+pub fn set_filter(texture: &Texture, filter: Filter) {
+    let mode = match filter {
+        Filter::Nearest => SDL_ScaleMode::SDL_ScaleModeNearest,
+        Filter::Linear => SDL_ScaleMode::SDL_ScaleModeLinear,
+    };
+
+    // SDL2 crate has no safe wrapper for texture scale mode.
+    unsafe {
+        sdl2::sys::SDL_SetTextureScaleMode(texture.raw(), mode);
+    }
+}
+
+/// Sets blend mode for textures with premultiplied alpha: source color added
+/// as is, destination scaled by inverse source alpha.
+fn set_premultiplied(texture: &Texture) {
+    // SDL2 crate has no safe wrapper for custom blend modes.
+    unsafe {
+        let mode = sdl2::sys::SDL_ComposeCustomBlendMode(
+            SDL_BlendFactor::SDL_BLENDFACTOR_ONE,
+            SDL_BlendFactor::SDL_BLENDFACTOR_ONE_MINUS_SRC_ALPHA,
+            SDL_BlendOperation::SDL_BLENDOPERATION_ADD,
+            SDL_BlendFactor::SDL_BLENDFACTOR_ONE,
+            SDL_BlendFactor::SDL_BLENDFACTOR_ONE_MINUS_SRC_ALPHA,
+            SDL_BlendOperation::SDL_BLENDOPERATION_ADD,
+        );
+
+        sdl2::sys::SDL_SetTextureBlendMode(texture.raw(), mode);
+    }
+}
@@ -1,34 +1,744 @@
 use std::fs::File;
 use std::path::Path;
 use std::path::PathBuf;
+use std::time::Duration;
 
 use serde::Deserialize;
 
 use crate::error::ApplicationResult;
+use crate::mapping::Input;
+use crate::remote;
+use crate::strings::Strings;
 
 #[derive(Debug, Deserialize)]
 pub struct Config {
-    background: PathBuf,
+    #[serde(default)]
+    background: Option<PathBuf>,
+    #[serde(default)]
+    width: Option<u32>,
+    #[serde(default)]
+    height: Option<u32>,
     sprites: Vec<Sprite>,
+    #[serde(default = "default_brightness")]
+    brightness: f32,
+    #[serde(default = "default_global_alpha")]
+    global_alpha: f32,
+    #[serde(default)]
+    grouping_ms: u64,
+    #[serde(default = "default_smoothing")]
+    smoothing: f32,
+    #[serde(default)]
+    default_selection: DefaultSelection,
+    #[serde(default = "default_deadzone")]
+    deadzone: f32,
+    #[serde(default)]
+    deadzones: Vec<AxisDeadzone>,
+    #[serde(default)]
+    axis_hold_ms: u64,
+    #[serde(default)]
+    text_anchor: TextAnchor,
+    #[serde(default)]
+    virtual_hat: Option<VirtualHat>,
+    #[serde(default)]
+    removal_grace_ms: u64,
+    #[serde(default)]
+    keyboard_mode: KeyboardMode,
+    #[serde(default)]
+    premultiplied_alpha: bool,
+    #[serde(default)]
+    remap: Vec<InputRemap>,
+    #[serde(default)]
+    setup_command: Option<Vec<String>>,
+    #[serde(default)]
+    layers: Vec<Layer>,
+    #[serde(default)]
+    splash: Option<Splash>,
+    #[serde(default = "default_save_interval_ms")]
+    save_interval_ms: u64,
+    #[serde(default)]
+    preferences_text: bool,
+    #[serde(default)]
+    axis_bands: Vec<AxisBand>,
+    #[serde(default = "default_cycle_key")]
+    cycle_key: String,
+    #[serde(default)]
+    strings: Strings,
+    #[serde(default)]
+    icons: Option<Icons>,
+    #[serde(default)]
+    foreground_title: Option<String>,
+    #[serde(default)]
+    hide_input: Option<Input>,
+    #[serde(default)]
+    offscreen: bool,
+    #[serde(default)]
+    osc: Option<OscTarget>,
+    #[serde(default = "default_max_inputs")]
+    max_inputs: usize,
+}
+
+fn default_max_inputs() -> usize {
+    16
+}
+
+/// Receiver of OSC messages with input and sprite states.
+#[derive(Debug, Deserialize)]
+pub struct OscTarget {
+    address: String,
+    #[serde(default = "default_osc_prefix")]
+    prefix: String,
+}
+
+impl OscTarget {
+    #[cfg_attr(not(feature = "osc"), allow(dead_code))]
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    #[cfg_attr(not(feature = "osc"), allow(dead_code))]
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+}
+
+fn default_osc_prefix() -> String {
+    "/show-gamepad".into()
+}
+
+/// Image with square icons of inputs, shown in setup instead of input names.
+#[derive(Debug, Deserialize)]
+pub struct Icons {
+    path: PathBuf,
+    size: u32,
+    inputs: Vec<InputIcon>,
+}
+
+impl Icons {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns width and height of icon cell in pixels.
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    pub fn inputs(&self) -> &[InputIcon] {
+        &self.inputs
+    }
+}
+
+/// Icon of input, cells counted left to right, top to bottom.
+#[derive(Debug, Deserialize)]
+pub struct InputIcon {
+    input: Input,
+    cell: u32,
+}
+
+impl InputIcon {
+    pub fn input(&self) -> &Input {
+        &self.input
+    }
+
+    pub fn cell(&self) -> u32 {
+        self.cell
+    }
+}
+
+/// Part of axis range, axis within band pressed as separate input.
+#[derive(Debug, Deserialize, Clone)]
+pub struct AxisBand {
+    axis: u32,
+    lo: f32,
+    hi: f32,
+}
+
+impl AxisBand {
+    pub fn axis(&self) -> u32 {
+        self.axis
+    }
+
+    pub fn lo(&self) -> f32 {
+        self.lo
+    }
+
+    pub fn hi(&self) -> f32 {
+        self.hi
+    }
+
+    pub fn contains(&self, position: f32) -> bool {
+        (self.lo..=self.hi).contains(&position)
+    }
+}
+
+fn default_save_interval_ms() -> u64 {
+    5000
+}
+
+fn default_cycle_key() -> String {
+    "F7".into()
+}
+
+/// Image shown on start before controller overlay.
+#[derive(Debug, Deserialize)]
+pub struct Splash {
+    path: PathBuf,
+    #[serde(default = "default_splash_ms")]
+    duration_ms: u64,
+}
+
+impl Splash {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn duration(&self) -> Duration {
+        Duration::from_millis(self.duration_ms)
+    }
+}
+
+fn default_splash_ms() -> u64 {
+    2000
+}
+
+/// Selects how keyboard inputs combine with controller inputs.
+#[derive(Debug, Deserialize, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyboardMode {
+    /// Keyboard becomes active device while any key is pressed.
+    #[default]
+    Override,
+    /// Keys are added to inputs of active controller, keyboard becomes active
+    /// device only when no controller input pressed.
+    Merge,
+}
+
+#[derive(Debug, Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "kebab-case")]
+pub enum TextAnchor {
+    #[default]
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl TextAnchor {
+    /// Returns corner on other side of the same window edge.
+    pub fn mirrored(self) -> Self {
+        match self {
+            TextAnchor::TopLeft => TextAnchor::TopRight,
+            TextAnchor::TopRight => TextAnchor::TopLeft,
+            TextAnchor::BottomLeft => TextAnchor::BottomRight,
+            TextAnchor::BottomRight => TextAnchor::BottomLeft,
+        }
+    }
+}
+
+/// Texture sampling used when sprite scaled.
+#[derive(Debug, Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum Filter {
+    Nearest,
+    Linear,
+}
+
+fn default_deadzone() -> f32 {
+    0.25
+}
+
+/// Selects default sprite when several default sprites belong to one group.
+#[derive(Debug, Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum DefaultSelection {
+    /// First declared sprite.
+    #[default]
+    First,
+    /// Last declared sprite.
+    Last,
+    /// Sprite with highest `z`, first declared if several sprites have same `z`.
+    Z,
+}
+
+fn default_brightness() -> f32 {
+    1.0
+}
+
+fn default_global_alpha() -> f32 {
+    1.0
+}
+
+fn default_smoothing() -> f32 {
+    1.0
 }
 
 impl Config {
-    pub fn background(&self) -> &Path {
-        &self.background
+    fn validate(&self) -> ApplicationResult<()> {
+        if self.width.is_some() != self.height.is_some() {
+            return Err("Both width and height must be defined".to_string().into());
+        }
+
+        if self.max_inputs == 0 {
+            return Err("Maximal number of inputs must be greater than zero"
+                .to_string()
+                .into());
+        }
+
+        if self.icons.as_ref().is_some_and(|icons| icons.size == 0) {
+            return Err("Icon size must be greater than zero".to_string().into());
+        }
+
+        if self.background.is_none() && self.size().is_none() {
+            return Err("Background or width and height must be defined"
+                .to_string()
+                .into());
+        }
+
+        if self
+            .size()
+            .is_some_and(|(width, height)| width == 0 || height == 0)
+        {
+            return Err("Window size must not be empty".to_string().into());
+        }
+
+        if !(0.0..=1.0).contains(&self.brightness) {
+            return Err(format!(
+                "Brightness must be in range from 0.0 to 1.0, but {} found",
+                self.brightness
+            )
+            .into());
+        }
+
+        if !(0.0..=1.0).contains(&self.global_alpha) {
+            return Err(format!(
+                "Global alpha must be in range from 0.0 to 1.0, but {} found",
+                self.global_alpha
+            )
+            .into());
+        }
+
+        if self.smoothing <= 0.0 || self.smoothing > 1.0 {
+            return Err(format!(
+                "Smoothing must be in range from 0.0 (exclusive) to 1.0, but {} found",
+                self.smoothing
+            )
+            .into());
+        }
+
+        if !(0.0..=1.0).contains(&self.deadzone) {
+            return Err(format!(
+                "Deadzone must be in range from 0.0 to 1.0, but {} found",
+                self.deadzone
+            )
+            .into());
+        }
+
+        for deadzone in &self.deadzones {
+            for value in [deadzone.deadzone, deadzone.min, deadzone.max]
+                .into_iter()
+                .flatten()
+            {
+                if !(0.0..=1.0).contains(&value) {
+                    return Err(format!(
+                        "Deadzone of axis {} must be in range from 0.0 to 1.0, but {} found",
+                        deadzone.axis, value
+                    )
+                    .into());
+                }
+            }
+        }
+
+        for band in &self.axis_bands {
+            if !(0.0..=1.0).contains(&band.lo)
+                || !(0.0..=1.0).contains(&band.hi)
+                || band.lo >= band.hi
+            {
+                return Err(format!(
+                    "Band of axis {} must be in range from 0.0 to 1.0 with lo less than hi",
+                    band.axis
+                )
+                .into());
+            }
+        }
+
+        for sprite in &self.sprites {
+            if let Some(layer) = sprite.layer() {
+                if !self.layers.iter().any(|known| known.name() == layer) {
+                    return Err(
+                        format!("Sprite {} uses unknown layer {}", sprite.name(), layer).into(),
+                    );
+                }
+            }
+
+            if let (Some(min), Some(max)) = (sprite.min_buttons, sprite.max_buttons) {
+                if min > max {
+                    return Err(format!(
+                        "Sprite {} has min_buttons greater than max_buttons",
+                        sprite.name()
+                    )
+                    .into());
+                }
+            }
+
+            if sprite.path.is_some() == sprite.primitive.is_some() {
+                return Err(format!(
+                    "Sprite {} must have either path or primitive",
+                    sprite.name()
+                )
+                .into());
+            }
+
+            if let Some(rect) = sprite.primitive().map(Primitive::rect) {
+                if rect.width() == 0 || rect.height() == 0 {
+                    return Err(format!("Primitive of sprite {} is empty", sprite.name()).into());
+                }
+            }
+
+            if let Some(rect) = sprite.rect() {
+                if rect.width() == Some(0) || rect.height() == Some(0) {
+                    return Err(format!("Rectangle of sprite {} is empty", sprite.name()).into());
+                }
+            }
+
+            if let Some(rumble) = sprite.rumble() {
+                if !(0.0..=1.0).contains(&rumble.low) || !(0.0..=1.0).contains(&rumble.high) {
+                    return Err(format!(
+                        "Rumble strength of sprite {} must be in range from 0.0 to 1.0",
+                        sprite.name()
+                    )
+                    .into());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Replaces URLs of background and sprites with paths to cached files.
+    pub fn resolve_urls(&mut self, cache: &Path) -> ApplicationResult<()> {
+        if let Some(ref background) = self.background {
+            self.background = Some(remote::resolve(background, cache)?);
+        }
+
+        if let Some(ref mut splash) = self.splash {
+            splash.path = remote::resolve(&splash.path, cache)?;
+        }
+
+        if let Some(ref mut icons) = self.icons {
+            icons.path = remote::resolve(&icons.path, cache)?;
+        }
+
+        for sprite in &mut self.sprites {
+            if let Some(ref path) = sprite.path {
+                sprite.path = Some(remote::resolve(path, cache)?);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn background(&self) -> Option<&Path> {
+        self.background.as_deref()
+    }
+
+    /// Returns window size defined in configuration.
+    pub fn size(&self) -> Option<(u32, u32)> {
+        self.width.zip(self.height)
     }
 
     pub fn sprites(&self) -> &[Sprite] {
         &self.sprites
     }
+
+    pub fn brightness(&self) -> f32 {
+        self.brightness
+    }
+
+    pub fn global_alpha(&self) -> f32 {
+        self.global_alpha
+    }
+
+    pub fn grouping(&self) -> Duration {
+        Duration::from_millis(self.grouping_ms)
+    }
+
+    pub fn smoothing(&self) -> f32 {
+        self.smoothing
+    }
+
+    pub fn default_selection(&self) -> DefaultSelection {
+        self.default_selection
+    }
+
+    pub fn deadzone(&self) -> f32 {
+        self.deadzone
+    }
+
+    pub fn deadzones(&self) -> &[AxisDeadzone] {
+        &self.deadzones
+    }
+
+    pub fn axis_hold(&self) -> Duration {
+        Duration::from_millis(self.axis_hold_ms)
+    }
+
+    pub fn text_anchor(&self) -> TextAnchor {
+        self.text_anchor
+    }
+
+    pub fn virtual_hat(&self) -> Option<&VirtualHat> {
+        self.virtual_hat.as_ref()
+    }
+
+    pub fn removal_grace(&self) -> Duration {
+        Duration::from_millis(self.removal_grace_ms)
+    }
+
+    pub fn keyboard_mode(&self) -> KeyboardMode {
+        self.keyboard_mode
+    }
+
+    pub fn premultiplied_alpha(&self) -> bool {
+        self.premultiplied_alpha
+    }
+
+    pub fn remap(&self) -> &[InputRemap] {
+        &self.remap
+    }
+
+    pub fn axis_bands(&self) -> &[AxisBand] {
+        &self.axis_bands
+    }
+
+    /// Returns name of key which cycles selected controller.
+    pub fn cycle_key(&self) -> &str {
+        &self.cycle_key
+    }
+
+    /// Checks that text copy of preferences saved with preferences.
+    pub fn preferences_text(&self) -> bool {
+        self.preferences_text
+    }
+
+    /// Returns maximal number of simultaneously pressed inputs.
+    pub fn max_inputs(&self) -> usize {
+        self.max_inputs
+    }
+
+    pub fn osc(&self) -> Option<&OscTarget> {
+        self.osc.as_ref()
+    }
+
+    /// Checks that frames drawn to offscreen texture of layout size before
+    /// scaling to window.
+    pub fn offscreen(&self) -> bool {
+        self.offscreen
+    }
+
+    /// Returns part of window title of application which must be in
+    /// foreground to show windows.
+    pub fn foreground_title(&self) -> Option<&str> {
+        self.foreground_title.as_deref()
+    }
+
+    /// Returns input which hides and shows window, controllers are read while
+    /// window hidden.
+    pub fn hide_input(&self) -> Option<&Input> {
+        self.hide_input.as_ref()
+    }
+
+    pub fn icons(&self) -> Option<&Icons> {
+        self.icons.as_ref()
+    }
+
+    pub fn strings(&self) -> &Strings {
+        &self.strings
+    }
+
+    pub fn save_interval(&self) -> Duration {
+        Duration::from_millis(self.save_interval_ms)
+    }
+
+    pub fn splash(&self) -> Option<&Splash> {
+        self.splash.as_ref()
+    }
+
+    pub fn layers(&self) -> &[Layer] {
+        &self.layers
+    }
+
+    /// Returns program with arguments to run when setup completed.
+    pub fn setup_command(&self) -> Option<&[String]> {
+        self.setup_command.as_deref()
+    }
+}
+
+/// Sprite layer enabled while modifier input pressed.
+#[derive(Debug, Deserialize)]
+pub struct Layer {
+    name: String,
+    modifier: Input,
+}
+
+impl Layer {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn modifier(&self) -> &Input {
+        &self.modifier
+    }
+}
+
+/// Replaces controller input with another input. Remap with controller GUID
+/// takes precedence over remap for all controllers.
+#[derive(Debug, Deserialize)]
+pub struct InputRemap {
+    #[serde(default)]
+    guid: Option<String>,
+    from: Input,
+    to: Input,
+}
+
+impl InputRemap {
+    pub fn guid(&self) -> Option<&str> {
+        self.guid.as_deref()
+    }
+
+    pub fn from(&self) -> &Input {
+        &self.from
+    }
+
+    pub fn to(&self) -> &Input {
+        &self.to
+    }
+}
+
+/// Hat synthesized from four D-pad buttons for controllers which do not
+/// report D-pad as hat. Without GUID applied to all controllers.
+#[derive(Debug, Deserialize, Clone)]
+pub struct VirtualHat {
+    #[serde(default)]
+    guid: Option<String>,
+    #[serde(default)]
+    hat: u32,
+    up: u32,
+    right: u32,
+    down: u32,
+    left: u32,
+}
+
+impl VirtualHat {
+    pub fn guid(&self) -> Option<&str> {
+        self.guid.as_deref()
+    }
+
+    pub fn hat(&self) -> u32 {
+        self.hat
+    }
+
+    pub fn up(&self) -> u32 {
+        self.up
+    }
+
+    pub fn right(&self) -> u32 {
+        self.right
+    }
+
+    pub fn down(&self) -> u32 {
+        self.down
+    }
+
+    pub fn left(&self) -> u32 {
+        self.left
+    }
+
+    /// Checks that button is one of hat buttons.
+    pub fn contains(&self, button: u32) -> bool {
+        [self.up, self.right, self.down, self.left].contains(&button)
+    }
+}
+
+/// Deadzone override for single axis. Override with controller GUID takes
+/// precedence over override for axis of all controllers. Thresholds of
+/// minimum and maximum directions take precedence over axis deadzone.
+#[derive(Debug, Deserialize)]
+pub struct AxisDeadzone {
+    #[serde(default)]
+    guid: Option<String>,
+    axis: u32,
+    #[serde(default)]
+    deadzone: Option<f32>,
+    #[serde(default)]
+    min: Option<f32>,
+    #[serde(default)]
+    max: Option<f32>,
+}
+
+impl AxisDeadzone {
+    pub fn guid(&self) -> Option<&str> {
+        self.guid.as_deref()
+    }
+
+    pub fn axis(&self) -> u32 {
+        self.axis
+    }
+
+    /// Returns threshold of minimum direction if defined.
+    pub fn min(&self) -> Option<f32> {
+        self.min.or(self.deadzone)
+    }
+
+    /// Returns threshold of maximum direction if defined.
+    pub fn max(&self) -> Option<f32> {
+        self.max.or(self.deadzone)
+    }
 }
 
 #[derive(Debug, Deserialize)]
 pub struct Sprite {
     group: usize,
     name: String,
-    path: PathBuf,
+    #[serde(default)]
+    path: Option<PathBuf>,
+    #[serde(default)]
+    primitive: Option<Primitive>,
     #[serde(default)]
     default: bool,
+    #[serde(default)]
+    bar: Option<Bar>,
+    #[serde(default)]
+    hold_ms: u64,
+    #[serde(default)]
+    sound: Option<PathBuf>,
+    #[serde(default)]
+    rumble: Option<Rumble>,
+    #[serde(default)]
+    partial: bool,
+    #[serde(default)]
+    z: i32,
+    #[serde(default)]
+    guid: Option<String>,
+    #[serde(default)]
+    exact: bool,
+    #[serde(default)]
+    layer: Option<String>,
+    #[serde(default)]
+    min_buttons: Option<usize>,
+    #[serde(default)]
+    max_buttons: Option<usize>,
+    #[serde(default)]
+    appear_ms: u64,
+    #[serde(default)]
+    player: Option<u8>,
+    #[serde(default)]
+    filter: Option<Filter>,
+    #[serde(default)]
+    counter: Option<Counter>,
+    #[serde(default)]
+    clip: Option<Rect>,
+    #[serde(default)]
+    rect: Option<Placement>,
 }
 
 impl Sprite {
@@ -40,13 +750,256 @@ impl Sprite {
         &self.name
     }
 
-    pub fn path(&self) -> &Path {
-        &self.path
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn primitive(&self) -> Option<&Primitive> {
+        self.primitive.as_ref()
     }
 
     pub fn default(&self) -> bool {
         self.default
     }
+
+    pub fn bar(&self) -> Option<&Bar> {
+        self.bar.as_ref()
+    }
+
+    pub fn hold(&self) -> Duration {
+        Duration::from_millis(self.hold_ms)
+    }
+
+    #[cfg_attr(not(feature = "sound"), allow(dead_code))]
+    pub fn sound(&self) -> Option<&Path> {
+        self.sound.as_deref()
+    }
+
+    pub fn rumble(&self) -> Option<&Rumble> {
+        self.rumble.as_ref()
+    }
+
+    pub fn partial(&self) -> bool {
+        self.partial
+    }
+
+    pub fn z(&self) -> i32 {
+        self.z
+    }
+
+    pub fn exact(&self) -> bool {
+        self.exact
+    }
+
+    pub fn layer(&self) -> Option<&str> {
+        self.layer.as_deref()
+    }
+
+    /// Returns player index which turns sprite on.
+    pub fn player(&self) -> Option<u8> {
+        self.player
+    }
+
+    pub fn clip(&self) -> Option<&Rect> {
+        self.clip.as_ref()
+    }
+
+    /// Returns destination rectangle of sprite, `None` to draw sprite over
+    /// whole window.
+    pub fn rect(&self) -> Option<&Placement> {
+        self.rect.as_ref()
+    }
+
+    pub fn counter(&self) -> Option<&Counter> {
+        self.counter.as_ref()
+    }
+
+    /// Returns texture filter of sprite, `None` to use renderer default.
+    pub fn filter(&self) -> Option<Filter> {
+        self.filter
+    }
+
+    pub fn appear(&self) -> Duration {
+        Duration::from_millis(self.appear_ms)
+    }
+
+    /// Returns minimal and maximal number of pressed inputs to show sprite.
+    pub fn pressed_limits(&self) -> Option<(Option<usize>, Option<usize>)> {
+        match (self.min_buttons, self.max_buttons) {
+            (None, None) => None,
+            limits => Some(limits),
+        }
+    }
+
+    /// Returns GUID of controller which must be connected to show sprite.
+    pub fn guid(&self) -> Option<&str> {
+        self.guid.as_deref()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Primitive {
+    shape: Shape,
+    color: [u8; 4],
+    rect: Rect,
+    #[serde(default = "default_thickness")]
+    thickness: u32,
+}
+
+fn default_thickness() -> u32 {
+    2
+}
+
+impl Primitive {
+    pub fn shape(&self) -> Shape {
+        self.shape
+    }
+
+    pub fn color(&self) -> [u8; 4] {
+        self.color
+    }
+
+    pub fn rect(&self) -> &Rect {
+        &self.rect
+    }
+
+    pub fn thickness(&self) -> u32 {
+        self.thickness
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum Shape {
+    Circle,
+    Rectangle,
+    Ring,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct Rumble {
+    low: f32,
+    high: f32,
+    duration_ms: u32,
+}
+
+impl Rumble {
+    pub fn low(&self) -> u16 {
+        (self.low * u16::MAX as f32) as u16
+    }
+
+    pub fn high(&self) -> u16 {
+        (self.high * u16::MAX as f32) as u16
+    }
+
+    pub fn duration_ms(&self) -> u32 {
+        self.duration_ms
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Bar {
+    axis: u32,
+    grow: Grow,
+    rect: Rect,
+}
+
+impl Bar {
+    pub fn axis(&self) -> u32 {
+        self.axis
+    }
+
+    pub fn grow(&self) -> Grow {
+        self.grow
+    }
+
+    pub fn rect(&self) -> &Rect {
+        &self.rect
+    }
+}
+
+/// Position of sprite press counter text.
+#[derive(Debug, Deserialize, Clone, Copy)]
+pub struct Counter {
+    x: i32,
+    y: i32,
+}
+
+impl Counter {
+    pub fn x(&self) -> i32 {
+        self.x
+    }
+
+    pub fn y(&self) -> i32 {
+        self.y
+    }
+}
+
+/// Destination rectangle of sprite. Missing position is taken from primitive
+/// or zero, missing size is taken from primitive or image size.
+#[derive(Debug, Deserialize, Clone, Copy)]
+pub struct Placement {
+    #[serde(default)]
+    x: Option<i32>,
+    #[serde(default)]
+    y: Option<i32>,
+    #[serde(default)]
+    width: Option<u32>,
+    #[serde(default)]
+    height: Option<u32>,
+}
+
+impl Placement {
+    pub fn x(&self) -> Option<i32> {
+        self.x
+    }
+
+    pub fn y(&self) -> Option<i32> {
+        self.y
+    }
+
+    pub fn width(&self) -> Option<u32> {
+        self.width
+    }
+
+    pub fn height(&self) -> Option<u32> {
+        self.height
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum Grow {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Rect {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+}
+
+impl Rect {
+    pub fn x(&self) -> i32 {
+        self.x
+    }
+
+    pub fn y(&self) -> i32 {
+        self.y
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
 }
 
 pub fn load<P>(path: P) -> ApplicationResult<Config>
@@ -54,7 +1007,8 @@ where
     P: AsRef<Path>,
 {
     let reader = File::open(path)?;
-    let config = serde_yaml::from_reader(reader)?;
+    let config: Config = serde_yaml::from_reader(reader)?;
+    config.validate()?;
 
     Ok(config)
 }
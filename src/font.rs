@@ -30,6 +30,23 @@ impl<'a> Font<'a> {
         })
     }
 
+    /// Returns width and height of text block in pixels.
+    pub fn size(&self, text: &str) -> (u32, u32) {
+        let lines = text.split('\n');
+        let height = lines.clone().count() as u32 * self.height;
+        let width = lines
+            .map(|line| {
+                line.chars()
+                    .filter(|&ch| (' '..'\x7f').contains(&ch))
+                    .count()
+            })
+            .max()
+            .unwrap_or(0) as u32
+            * self.width;
+
+        (width, height)
+    }
+
     pub fn write(
         &self,
         canvas: &mut WindowCanvas,
@@ -0,0 +1,32 @@
+/// Returns title of foreground window of any application, `None` if title
+/// can not be read or platform is not supported.
+#[cfg(windows)]
+pub fn title() -> Option<String> {
+    use std::ffi::c_void;
+
+    #[link(name = "user32")]
+    extern "system" {
+        fn GetForegroundWindow() -> *mut c_void;
+        fn GetWindowTextW(window: *mut c_void, text: *mut u16, length: i32) -> i32;
+    }
+
+    let mut buffer = [0u16; 512];
+
+    // Foreground window title is available only through WinAPI.
+    let length = unsafe {
+        let window = GetForegroundWindow();
+
+        if window.is_null() {
+            return None;
+        }
+
+        GetWindowTextW(window, buffer.as_mut_ptr(), buffer.len() as i32)
+    };
+
+    Some(String::from_utf16_lossy(&buffer[..length.max(0) as usize]))
+}
+
+#[cfg(not(windows))]
+pub fn title() -> Option<String> {
+    None
+}
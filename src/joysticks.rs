@@ -1,12 +1,27 @@
+use std::collections::BTreeSet;
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::fs;
+use std::fs::File;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::Instant;
 
 use sdl2::joystick::HatState;
 use sdl2::joystick::Joystick;
 use sdl2::JoystickSubsystem;
 
+use crate::config::AxisBand;
+use crate::config::Config;
+use crate::config::KeyboardMode;
+use crate::config::VirtualHat;
 use crate::error::ApplicationResult;
 use crate::mapping::Input;
+use crate::mapping::PinnedAxis;
+use crate::mapping::State;
+use crate::remap::Remap;
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
 struct GuidAxis {
@@ -39,23 +54,70 @@ impl AxisLimits {
         }
     }
 
+    pub fn pinned(pinned: &PinnedAxis) -> Self {
+        Self {
+            default: pinned.default(),
+            min: pinned.min(),
+            max: pinned.max(),
+        }
+    }
+
     pub fn extend(&mut self, value: i16) {
         self.min = self.min.min(value as i32);
         self.max = self.max.max(value as i32);
     }
 
-    pub fn zone(&self, value: i16) -> AxisZone {
-        let bound = (self.min).max(self.max) / 4;
+    pub fn position(&self, value: i16) -> f32 {
+        match self.max - self.min {
+            0 => 0.0,
+            range => (value as i32 - self.min) as f32 / range as f32,
+        }
+    }
+
+    pub fn deflection(&self, value: i16) -> f32 {
+        match self.max - self.min {
+            0 => 0.0,
+            range => (value as i32 - self.default).abs() as f32 / range as f32,
+        }
+    }
+
+    /// Returns zone of axis value. Thresholds are measured from default
+    /// value to observed limit of direction, so axes resting near extreme
+    /// (pedals, triggers) are detected too.
+    pub fn zone(&self, value: i16, thresholds: AxisThresholds) -> AxisZone {
+        let min_bound = ((self.default - self.min) as f32 * thresholds.min) as i32;
+        let max_bound = ((self.max - self.default) as f32 * thresholds.max) as i32;
 
         match value as i32 {
-            v if (v - self.default).abs() < bound => AxisZone::Default,
-            v if v < self.default => AxisZone::Min,
-            v if v > self.default => AxisZone::Max,
+            v if v < self.default && self.default - v >= min_bound => AxisZone::Min,
+            v if v > self.default && v - self.default >= max_bound => AxisZone::Max,
             _ => AxisZone::Default,
         }
     }
 }
 
+/// Activation thresholds of axis directions, fractions of travel from default
+/// value to direction limit.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AxisThresholds {
+    min: f32,
+    max: f32,
+}
+
+impl AxisThresholds {
+    pub fn new(min: f32, max: f32) -> Self {
+        Self { min, max }
+    }
+
+    /// Replaces thresholds with defined overrides.
+    fn with(self, min: Option<f32>, max: Option<f32>) -> Self {
+        Self {
+            min: min.unwrap_or(self.min),
+            max: max.unwrap_or(self.max),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum AxisZone {
     Min,
@@ -66,22 +128,180 @@ pub enum AxisZone {
 #[derive(Debug)]
 struct JoustickLimits {
     limits: HashMap<GuidAxis, AxisLimits>,
+    pinned: HashSet<GuidAxis>,
+    deadzone: f32,
+    axis_deadzones: HashMap<u32, (Option<f32>, Option<f32>)>,
+    guid_deadzones: HashMap<GuidAxis, (Option<f32>, Option<f32>)>,
 }
 
 impl JoustickLimits {
-    pub fn new() -> Self {
+    pub fn new(config: &Config) -> Self {
+        let mut axis_deadzones = HashMap::new();
+        let mut guid_deadzones = HashMap::new();
+
+        for deadzone in config.deadzones() {
+            match deadzone.guid() {
+                Some(guid) => {
+                    let key = GuidAxis::new(guid, deadzone.axis());
+
+                    guid_deadzones.insert(key, (deadzone.min(), deadzone.max()));
+                }
+                None => {
+                    axis_deadzones.insert(deadzone.axis(), (deadzone.min(), deadzone.max()));
+                }
+            }
+        }
+
         Self {
             limits: HashMap::new(),
+            pinned: HashSet::new(),
+            deadzone: config.deadzone(),
+            axis_deadzones,
+            guid_deadzones,
+        }
+    }
+
+    /// Returns thresholds for axis directions: controller specific override,
+    /// then axis override, then global deadzone.
+    fn thresholds(&self, key: &GuidAxis) -> AxisThresholds {
+        let mut thresholds = AxisThresholds::new(self.deadzone, self.deadzone);
+
+        for &(min, max) in [
+            self.axis_deadzones.get(&key.axis),
+            self.guid_deadzones.get(key),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            thresholds = thresholds.with(min, max);
         }
+
+        thresholds
     }
 
+    /// Clears learned limits, pinned limits are kept.
     pub fn reset(&mut self) {
-        self.limits.clear();
+        let pinned = &self.pinned;
+        self.limits.retain(|key, _| pinned.contains(key));
+    }
+
+    /// Removes all pinned limits, axes learn limits again.
+    pub fn unpin_all(&mut self) {
+        for key in self.pinned.drain() {
+            self.limits.remove(&key);
+        }
+    }
+
+    /// Sets fixed limits of axis, such limits do not extend.
+    pub fn pin(&mut self, guid: &str, pinned: &PinnedAxis) {
+        let key = GuidAxis::new(guid, pinned.axis());
+
+        self.limits.insert(key.clone(), AxisLimits::pinned(pinned));
+        self.pinned.insert(key);
+    }
+
+    /// Loads limits learned in previous sessions, pinned limits are kept.
+    /// With GUID only limits of this controller loaded.
+    pub fn load<P>(&mut self, path: P, only: Option<&str>) -> ApplicationResult<()>
+    where
+        P: AsRef<Path>,
+    {
+        let learned = read_limits(path)?;
+
+        for (guid, limits) in learned {
+            if only.is_some_and(|only| only != guid) {
+                continue;
+            }
+
+            for limit in limits {
+                let key = GuidAxis::new(&guid, limit.axis());
+
+                if !self.pinned.contains(&key) {
+                    self.limits.insert(key, AxisLimits::pinned(&limit));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Saves learned limits of all controllers, pinned limits are saved in
+    /// preferences. Saved limits of other controllers are kept, so windows
+    /// showing different controllers do not overwrite each other.
+    pub fn save<P>(&self, path: P) -> ApplicationResult<()>
+    where
+        P: AsRef<Path>,
+    {
+        let mut learned: HashMap<String, Vec<PinnedAxis>> = HashMap::new();
+
+        for (key, limits) in &self.limits {
+            if !self.pinned.contains(key) {
+                learned
+                    .entry(key.giud.clone())
+                    .or_default()
+                    .push(PinnedAxis::new(
+                        key.axis,
+                        limits.default,
+                        limits.min,
+                        limits.max,
+                    ));
+            }
+        }
+
+        for limits in learned.values_mut() {
+            limits.sort_by_key(PinnedAxis::axis);
+        }
+
+        let mut saved = read_limits(&path).unwrap_or_default();
+        saved.extend(learned);
+
+        write_limits(path, &saved)
+    }
+
+    /// Returns GUIDs of controllers with learned limits.
+    fn learned(&self) -> HashSet<String> {
+        self.limits
+            .keys()
+            .filter(|key| !self.pinned.contains(key))
+            .map(|key| key.giud.clone())
+            .collect()
+    }
+
+    /// Clears learned limits and removes them from file, saved limits of
+    /// other controllers are kept.
+    pub fn forget<P>(&mut self, path: P) -> ApplicationResult<()>
+    where
+        P: AsRef<Path>,
+    {
+        let learned = self.learned();
+        self.reset();
+
+        let mut saved = read_limits(&path).unwrap_or_default();
+        saved.retain(|guid, _| !learned.contains(guid));
+
+        write_limits(path, &saved)
+    }
+
+    /// Returns limits of all axes of controller with given GUID.
+    pub fn limits(&self, guid: &str) -> Vec<PinnedAxis> {
+        let mut limits: Vec<_> = self
+            .limits
+            .iter()
+            .filter(|(key, _)| key.giud == guid)
+            .map(|(key, limits)| PinnedAxis::new(key.axis, limits.default, limits.min, limits.max))
+            .collect();
+        limits.sort_by_key(PinnedAxis::axis);
+
+        limits
     }
 
     pub fn update(&mut self, guid: &str, axis: u32, value: i16) {
         let key = GuidAxis::new(guid, axis);
 
+        if self.pinned.contains(&key) {
+            return;
+        }
+
         self.limits
             .entry(key)
             .or_insert_with(|| AxisLimits::new(value))
@@ -93,68 +313,416 @@ impl JoustickLimits {
 
         self.limits
             .get(&key)
-            .map(|limits| limits.zone(value))
+            .map(|limits| limits.zone(value, self.thresholds(&key)))
             .unwrap_or(AxisZone::Default)
     }
+
+    pub fn get(&self, guid: &str, axis: u32) -> Option<&AxisLimits> {
+        let key = GuidAxis::new(guid, axis);
+
+        self.limits.get(&key)
+    }
+}
+
+/// Exponential moving average filter for axis values. Factor 1.0 disables
+/// smoothing, smaller factors give more stable but slower values. Values are
+/// kept per device identity, so identical controllers are filtered separately.
+#[derive(Debug)]
+struct AxisSmoothing {
+    factor: f32,
+    values: HashMap<GuidAxis, f32>,
+}
+
+impl AxisSmoothing {
+    pub fn new(factor: f32) -> Self {
+        Self {
+            factor,
+            values: HashMap::new(),
+        }
+    }
+
+    pub fn filter(&mut self, device: &str, axis: u32, value: i16) -> i16 {
+        let key = GuidAxis::new(device, axis);
+        let factor = self.factor;
+        let smoothed = self
+            .values
+            .entry(key)
+            .and_modify(|smoothed| *smoothed += factor * (value as f32 - *smoothed))
+            .or_insert(value as f32);
+
+        smoothed.round() as i16
+    }
+
+    /// Forgets values of removed device.
+    pub fn remove(&mut self, device: &str) {
+        self.values.retain(|key, _| key.giud != device);
+    }
+}
+
+#[derive(Debug)]
+struct AxisPosition {
+    position: f32,
+    deflection: f32,
 }
 
 pub struct Joysticks {
+    device: Option<String>,
+    selected: Option<String>,
     active: Option<String>,
+    active_device: Option<String>,
+    ordinals: HashMap<u32, u32>,
     pressed: HashSet<Input>,
+    positions: HashMap<u32, AxisPosition>,
     keyboard: HashSet<Input>,
     joysticks: HashMap<u32, Joystick>,
     limits: JoustickLimits,
+    limits_path: PathBuf,
+    smoothing: AxisSmoothing,
+    axis_hold: Duration,
+    axis_seen: HashMap<(String, Input), Instant>,
+    virtual_hat: Option<VirtualHat>,
+    removal_grace: Duration,
+    removed: HashMap<String, RemovedJoystick>,
+    buttons: HashMap<String, ButtonsSeen>,
+    keyboard_mode: KeyboardMode,
+    remap: Remap,
+    axis_bands: Vec<AxisBand>,
+    max_inputs: usize,
+    overflow: Option<usize>,
+    consumed: bool,
 }
 
-impl Joysticks {
-    pub fn create(joystick_subsystem: &JoystickSubsystem) -> ApplicationResult<Self> {
-        let mut joysticks = HashMap::new();
+/// Number of buttons reported by controller and indices of all buttons seen
+/// pressed, helps to find indices of extra buttons like paddles.
+#[derive(Debug, Default)]
+pub struct ButtonsSeen {
+    count: u32,
+    seen: BTreeSet<u32>,
+}
 
-        for id in 0..joystick_subsystem.num_joysticks()? {
-            let joystick = joystick_subsystem.open(id)?;
+impl ButtonsSeen {
+    pub fn count(&self) -> u32 {
+        self.count
+    }
 
-            joysticks.insert(id, joystick);
-        }
+    pub fn seen(&self) -> &BTreeSet<u32> {
+        &self.seen
+    }
+}
 
-        Ok(Self {
+/// Controller removed during grace period, its last pressed inputs are kept
+/// until controller returns or grace period ends.
+#[derive(Debug)]
+struct RemovedJoystick {
+    removed: Instant,
+    pressed: HashSet<Input>,
+}
+
+impl Joysticks {
+    pub fn create(
+        config: &Config,
+        joystick_subsystem: &JoystickSubsystem,
+        device: Option<&str>,
+        limits_path: &Path,
+    ) -> ApplicationResult<Self> {
+        let mut joysticks = Self {
+            device: device.map(Into::into),
+            selected: None,
             active: None,
+            active_device: None,
+            ordinals: HashMap::new(),
             keyboard: HashSet::new(),
             pressed: HashSet::new(),
-            joysticks,
-            limits: JoustickLimits::new(),
-        })
+            positions: HashMap::new(),
+            joysticks: HashMap::new(),
+            limits: JoustickLimits::new(config),
+            limits_path: limits_path.into(),
+            smoothing: AxisSmoothing::new(config.smoothing()),
+            axis_hold: config.axis_hold(),
+            axis_seen: HashMap::new(),
+            virtual_hat: config.virtual_hat().cloned(),
+            removal_grace: config.removal_grace(),
+            removed: HashMap::new(),
+            buttons: HashMap::new(),
+            keyboard_mode: config.keyboard_mode(),
+            remap: Remap::new(config.remap()),
+            axis_bands: config.axis_bands().to_vec(),
+            max_inputs: config.max_inputs(),
+            overflow: None,
+            consumed: false,
+        };
+
+        for index in 0..joystick_subsystem.num_joysticks()? {
+            joysticks.add(joystick_subsystem, index)?;
+        }
+
+        Ok(joysticks)
+    }
+
+    fn accepts(&self, joystick: &Joystick) -> bool {
+        match self.device {
+            Some(ref device) => joystick.guid().to_string() == device_guid(device),
+            None => true,
+        }
+    }
+
+    /// Returns device identity of joystick: GUID for first controller with
+    /// this GUID, GUID with ordinal (`GUID#2`) for other identical controllers.
+    fn identity(&self, id: u32, joystick: &Joystick) -> String {
+        match self.ordinals.get(&id) {
+            Some(&ordinal) if ordinal > 1 => format!("{}#{}", joystick.guid(), ordinal),
+            _ => joystick.guid().to_string(),
+        }
     }
 
+    /// Returns GUID of active controller, used to find mapping.
     pub fn active(&self) -> Option<&String> {
         self.active.as_ref()
     }
 
+    /// Returns identity of controller which input received last, identical
+    /// controllers have different identities.
+    pub fn active_device(&self) -> Option<&String> {
+        self.active_device.as_ref()
+    }
+
+    /// Returns identity of controller selected by user, only selected
+    /// controller is shown while selection is set and controller connected.
+    pub fn selected(&self) -> Option<&String> {
+        self.selected.as_ref()
+    }
+
+    pub fn set_selected(&mut self, device: Option<String>) {
+        self.selected = device;
+    }
+
+    /// Checks that device selected. Any device is selected when selected
+    /// controller is not connected.
+    fn is_selected(&self, device: &str) -> bool {
+        match self.selected {
+            Some(ref selected) if selected != device => !self
+                .joysticks
+                .iter()
+                .any(|(&id, joystick)| self.identity(id, joystick) == *selected),
+            _ => true,
+        }
+    }
+
+    /// Checks that device with given identity shown, device option with
+    /// ordinal accepts only one of identical controllers.
+    fn is_shown(&self, device: &str) -> bool {
+        let shown = match self.device {
+            Some(ref filter) if filter.contains('#') => filter == device,
+            _ => true,
+        };
+
+        shown && self.is_selected(device)
+    }
+
     pub fn pressed(&self) -> &HashSet<Input> {
         &self.pressed
     }
 
+    /// Returns names and GUIDs of connected controllers sorted by name.
+    pub fn controllers(&self) -> Vec<(String, String)> {
+        let mut controllers: Vec<_> = self
+            .joysticks
+            .values()
+            .map(|joystick| (joystick.name(), joystick.guid().to_string()))
+            .collect();
+        controllers.sort();
+
+        controllers
+    }
+
+    /// Returns names and identities of connected controllers sorted by name,
+    /// identical controllers listed separately.
+    pub fn devices(&self) -> Vec<(String, String)> {
+        let mut devices: Vec<_> = self
+            .joysticks
+            .iter()
+            .map(|(&id, joystick)| (joystick.name(), self.identity(id, joystick)))
+            .collect();
+        devices.sort();
+
+        devices
+    }
+
+    /// Returns player index of connected controller with given GUID. Player
+    /// index is known only for some controllers, e.g. XInput on Windows.
+    pub fn player_index(&self, guid: &str) -> Option<u8> {
+        self.joysticks
+            .values()
+            .filter(|joystick| joystick.guid().to_string() == guid)
+            .find_map(|joystick| {
+                // SDL2 crate has no safe wrapper for player index.
+                let index = unsafe {
+                    let raw = sdl2::sys::SDL_JoystickFromInstanceID(joystick.instance_id() as i32);
+
+                    sdl2::sys::SDL_JoystickGetPlayerIndex(raw)
+                };
+
+                u8::try_from(index).ok()
+            })
+    }
+
+    /// Returns raw values of all axes of controller with given GUID, without
+    /// smoothing and limits.
+    pub fn axes(&self, guid: &str) -> ApplicationResult<Option<Vec<i16>>> {
+        match self
+            .joysticks
+            .values()
+            .find(|joystick| joystick.guid().to_string() == guid)
+        {
+            Some(joystick) => {
+                let mut axes = Vec::new();
+
+                for axis in 0..joystick.num_axes() {
+                    axes.push(joystick.axis(axis)?);
+                }
+
+                Ok(Some(axes))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Returns buttons seen pressed on controller with given GUID.
+    pub fn buttons(&self, guid: &str) -> Option<&ButtonsSeen> {
+        self.buttons.get(guid)
+    }
+
+    /// Returns normalized position of axis from most deflected joystick.
+    pub fn position(&self, axis: u32) -> Option<f32> {
+        self.positions.get(&axis).map(|position| position.position)
+    }
+
+    /// Opens controller with given device index. Controllers are stored by
+    /// instance id, which is used in removal events.
     pub fn add(
         &mut self,
         joystick_subsystem: &JoystickSubsystem,
-        id: u32,
+        index: u32,
     ) -> ApplicationResult<()> {
-        let joystick = joystick_subsystem.open(id)?;
+        let joystick = joystick_subsystem.open(index)?;
+        let id = joystick.instance_id();
+
+        if self.accepts(&joystick) && !self.joysticks.contains_key(&id) {
+            let guid = joystick.guid().to_string();
+            let used: HashSet<_> = self
+                .joysticks
+                .iter()
+                .filter(|(_, other)| other.guid().to_string() == guid)
+                .filter_map(|(other, _)| self.ordinals.get(other))
+                .collect();
+            let ordinal = (1..).find(|ordinal| !used.contains(ordinal)).unwrap_or(1);
 
-        self.joysticks.insert(id, joystick);
+            self.removed.remove(&guid);
+            self.ordinals.insert(id, ordinal);
+            self.joysticks.insert(id, joystick);
+        }
 
         Ok(())
     }
 
+    /// Checks that controller with given GUID is connected or removed
+    /// within grace period.
+    pub fn connected(&self, guid: &str) -> bool {
+        self.removed.contains_key(guid)
+            || self
+                .joysticks
+                .values()
+                .any(|joystick| joystick.guid().to_string() == guid)
+    }
+
+    /// Removes controller with given instance id. With removal grace period
+    /// controller state is kept until it returns or grace period ends.
     pub fn remove(&mut self, id: u32) {
-        self.joysticks.remove(&id);
+        if let Some(joystick) = self.joysticks.remove(&id) {
+            let guid = joystick.guid().to_string();
+            self.smoothing.remove(&self.identity(id, &joystick));
+            self.ordinals.remove(&id);
+
+            if !self.removal_grace.is_zero() {
+                let pressed = match self.active {
+                    Some(ref active) if *active == guid => self
+                        .pressed
+                        .iter()
+                        .filter(|input| !matches!(input, Input::Key { .. }))
+                        .cloned()
+                        .collect(),
+                    _ => HashSet::new(),
+                };
+
+                self.removed.insert(
+                    guid,
+                    RemovedJoystick {
+                        removed: Instant::now(),
+                        pressed,
+                    },
+                );
+            }
+        }
+    }
+
+    /// Keeps pressed inputs of controllers removed within grace period.
+    fn hold_removed(&mut self, now: Instant) {
+        let removal_grace = self.removal_grace;
+        self.removed
+            .retain(|_, removed| now.duration_since(removed.removed) < removal_grace);
+
+        for (guid, removed) in &self.removed {
+            if !removed.pressed.is_empty() && self.is_selected(guid) {
+                self.pressed.extend(removed.pressed.iter().cloned());
+                self.active.get_or_insert_with(|| guid.clone());
+            }
+        }
+    }
+
+    pub fn rumble(&mut self, guid: &str, low: u16, high: u16, duration_ms: u32) {
+        for joystick in self.joysticks.values_mut() {
+            if joystick.has_rumble() && joystick.guid().to_string() == guid {
+                let _ = joystick.set_rumble(low, high, duration_ms);
+            }
+        }
     }
 
     pub fn released(&self) -> bool {
         self.pressed.is_empty()
     }
 
-    pub fn reset_limits(&mut self) {
-        self.limits.reset();
+    /// Clears learned limits and removes them from saved limits file.
+    pub fn reset_limits(&mut self) -> ApplicationResult<()> {
+        self.limits.forget(&self.limits_path)
+    }
+
+    /// Loads limits learned in previous sessions if they were saved.
+    pub fn load_limits(&mut self) -> ApplicationResult<()> {
+        if !self.limits_path.exists() {
+            return Ok(());
+        }
+
+        let only = self.device.as_deref().map(device_guid);
+
+        self.limits.load(&self.limits_path, only)
+    }
+
+    /// Saves learned limits to be loaded on next start.
+    pub fn save_limits(&self) -> ApplicationResult<()> {
+        self.limits.save(&self.limits_path)
+    }
+
+    pub fn pin_limits(&mut self, guid: &str, pinned: &PinnedAxis) {
+        self.limits.pin(guid, pinned);
+    }
+
+    pub fn unpin_limits(&mut self) {
+        self.limits.unpin_all();
+    }
+
+    /// Returns current limits of all axes of controller in pinnable form.
+    pub fn limits(&self, guid: &str) -> Vec<PinnedAxis> {
+        self.limits.limits(guid)
     }
 
     pub fn key_down(&mut self, key: &str) {
@@ -165,37 +733,160 @@ impl Joysticks {
         self.keyboard.remove(&Input::key(key));
     }
 
+    /// Reads joysticks state and returns all inputs pressed since previous
+    /// update, including inputs released between polls.
+    /// Inputs over maximal number are dropped, see `overflow`.
     pub fn update(&mut self) -> ApplicationResult<()> {
-        self.pressed.clear();
-        self.active = None;
+        self.poll()?;
+        self.consumed = true;
+        self.overflow = None;
+
+        if self.pressed.len() > self.max_inputs {
+            let count = self.pressed.len();
+            let mut inputs: Vec<_> = self.pressed.drain().collect();
+            inputs.sort_by_key(|input| input.to_string());
+            inputs.truncate(self.max_inputs);
+
+            self.pressed.extend(inputs);
+            self.overflow = Some(count);
+        }
+
+        Ok(())
+    }
+
+    /// Returns number of pressed inputs when it exceeded maximum during last
+    /// update, which usually means faulty controller.
+    pub fn overflow(&self) -> Option<usize> {
+        self.overflow
+    }
 
-        for joystick in self.joysticks.values() {
+    /// Keeps axis inputs pressed during axis hold time after release,
+    /// so short axis drop outs do not break combinations.
+    fn hold_axes(&mut self, now: Instant) {
+        let axis_hold = self.axis_hold;
+        self.axis_seen
+            .retain(|_, seen| now.duration_since(*seen) < axis_hold);
+
+        for (guid, input) in self.axis_seen.keys() {
+            let axis_pressed = match input {
+                Input::Axis { axis, .. } => self.pressed.iter().any(|pressed| {
+                    matches!(pressed, Input::Axis { axis: pressed_axis, .. } if pressed_axis == axis)
+                }),
+                _ => false,
+            };
+
+            if !axis_pressed {
+                self.pressed.insert(input.clone());
+                self.active.get_or_insert_with(|| guid.clone());
+            }
+        }
+    }
+
+    /// Reads joysticks state and accumulates pressed inputs until next update.
+    pub fn poll(&mut self) -> ApplicationResult<()> {
+        if self.consumed {
+            self.pressed.clear();
+            self.active = None;
+            self.consumed = false;
+        }
+
+        self.positions.clear();
+
+        let now = Instant::now();
+
+        for (&id, joystick) in &self.joysticks {
             let guid = joystick.guid().to_string();
+            let device = self.identity(id, joystick);
+
+            if !self.is_shown(&device) {
+                continue;
+            }
 
             for axis in 0..joystick.num_axes() {
                 let value = joystick.axis(axis)?;
+                let value = self.smoothing.filter(&device, axis, value);
                 self.limits.update(&guid, axis, value);
                 let zone = self.limits.zone(&guid, axis, value);
 
-                match zone {
-                    AxisZone::Min => {
-                        self.pressed.insert(Input::axis_min(axis));
+                if let Some(limits) = self.limits.get(&guid, axis) {
+                    let position = AxisPosition {
+                        position: limits.position(value),
+                        deflection: limits.deflection(value),
+                    };
+
+                    for input in band_inputs(&self.axis_bands, axis, position.position, &zone) {
+                        self.pressed.insert(self.remap.apply(&guid, input));
                         self.active = Some(guid.clone());
+                        self.active_device = Some(device.clone());
                     }
-                    AxisZone::Max => {
-                        self.pressed.insert(Input::axis_max(axis));
-                        self.active = Some(guid.clone());
+
+                    match self.positions.get(&axis) {
+                        Some(current) if current.deflection >= position.deflection => {}
+                        _ => {
+                            self.positions.insert(axis, position);
+                        }
                     }
-                    _ => {}
+                }
+
+                let input = match zone {
+                    AxisZone::Min => Some(Input::axis_min(axis)),
+                    AxisZone::Max => Some(Input::axis_max(axis)),
+                    AxisZone::Default => None,
+                };
+
+                if let Some(input) = input {
+                    let input = self.remap.apply(&guid, input);
+                    self.axis_seen.insert((guid.clone(), input.clone()), now);
+                    self.pressed.insert(input);
+                    self.active = Some(guid.clone());
+                    self.active_device = Some(device.clone());
                 }
             }
 
+            let virtual_hat = self
+                .virtual_hat
+                .as_ref()
+                .filter(|hat| hat.guid().is_none_or(|hat_guid| hat_guid == guid));
+
+            if let Some(virtual_hat) = virtual_hat {
+                let button = |button| -> ApplicationResult<bool> {
+                    Ok(button < joystick.num_buttons() && joystick.button(button)?)
+                };
+                let state = hat_state(
+                    button(virtual_hat.up())?,
+                    button(virtual_hat.right())?,
+                    button(virtual_hat.down())?,
+                    button(virtual_hat.left())?,
+                );
+
+                if state != State::Center {
+                    let input = Input::hat(virtual_hat.hat(), state);
+                    self.pressed.insert(self.remap.apply(&guid, input));
+                    self.active = Some(guid.clone());
+                    self.active_device = Some(device.clone());
+                }
+            }
+
+            let buttons_seen = self.buttons.entry(guid.clone()).or_default();
+            buttons_seen.count = joystick.num_buttons();
+
             for button in 0..joystick.num_buttons() {
                 let guid = joystick.guid().to_string();
+                let pressed = joystick.button(button)?;
+
+                if pressed {
+                    buttons_seen.seen.insert(button);
+                }
+
+                if virtual_hat.is_some_and(|hat| hat.contains(button)) {
+                    continue;
+                }
 
-                if joystick.button(button)? {
-                    self.pressed.insert(Input::button(button));
+                if pressed {
+                    let input = Input::button(button);
+                    self.pressed.insert(self.remap.apply(&guid, input));
                     self.active = Some(guid);
+                    self.active_device = Some(device.clone());
                 }
             }
 
@@ -204,17 +895,313 @@ impl Joysticks {
                 let state = joystick.hat(hat)?;
 
                 if state != HatState::Centered {
-                    self.pressed.insert(Input::hat(hat, state));
+                    let input = Input::hat(hat, state);
+                    self.pressed.insert(self.remap.apply(&guid, input));
                     self.active = Some(guid);
+                    self.active_device = Some(device.clone());
                 }
             }
         }
 
+        self.hold_axes(now);
+        self.hold_removed(now);
+
         if !self.keyboard.is_empty() {
             self.pressed.extend(self.keyboard.iter().cloned());
-            self.active = Some("Keyboard".into());
+
+            match self.keyboard_mode {
+                KeyboardMode::Override => self.active = Some("Keyboard".into()),
+                KeyboardMode::Merge => {
+                    self.active.get_or_insert_with(|| "Keyboard".into());
+                }
+            }
         }
 
         Ok(())
     }
 }
+
+/// Returns GUID part of device identity.
+pub fn device_guid(device: &str) -> &str {
+    device.split('#').next().unwrap_or(device)
+}
+
+/// Reads saved learned limits of controllers by GUID.
+fn read_limits<P>(path: P) -> ApplicationResult<HashMap<String, Vec<PinnedAxis>>>
+where
+    P: AsRef<Path>,
+{
+    let reader = File::open(path)?;
+
+    Ok(serde_yaml::from_reader(reader)?)
+}
+
+/// Writes learned limits of controllers, file removed when no limits left.
+fn write_limits<P>(path: P, limits: &HashMap<String, Vec<PinnedAxis>>) -> ApplicationResult<()>
+where
+    P: AsRef<Path>,
+{
+    if limits.is_empty() {
+        return match fs::remove_file(path) {
+            Err(error) if error.kind() != ErrorKind::NotFound => Err(error.into()),
+            _ => Ok(()),
+        };
+    }
+
+    let writer = File::create(path)?;
+    serde_yaml::to_writer(writer, limits)?;
+
+    Ok(())
+}
+
+/// Returns inputs of axis bands containing axis position. Bands pressed only
+/// while axis deflected out of default zone, so resting axis never pressed.
+fn band_inputs(bands: &[AxisBand], axis: u32, position: f32, zone: &AxisZone) -> Vec<Input> {
+    if matches!(zone, AxisZone::Default) {
+        return Vec::new();
+    }
+
+    bands
+        .iter()
+        .filter(|band| band.axis() == axis && band.contains(position))
+        .map(|band| {
+            let lo = (band.lo() * 100.0).round() as u8;
+            let hi = (band.hi() * 100.0).round() as u8;
+
+            Input::axis_band(axis, lo, hi)
+        })
+        .collect()
+}
+
+/// Combines four D-pad buttons to hat state, opposite directions cancel each
+/// other.
+fn hat_state(up: bool, right: bool, down: bool, left: bool) -> State {
+    match (up && !down, right && !left, down && !up, left && !right) {
+        (true, true, _, _) => State::RightUp,
+        (_, true, true, _) => State::RightDown,
+        (true, _, _, true) => State::LeftUp,
+        (_, _, true, true) => State::LeftDown,
+        (true, _, _, _) => State::Up,
+        (_, true, _, _) => State::Right,
+        (_, _, true, _) => State::Down,
+        (_, _, _, true) => State::Left,
+        _ => State::Center,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits() -> AxisLimits {
+        let mut limits = AxisLimits::new(0);
+        limits.extend(-10_000);
+        limits.extend(10_000);
+
+        limits
+    }
+
+    fn zone(limits: &AxisLimits, value: i16, thresholds: AxisThresholds) -> &'static str {
+        match limits.zone(value, thresholds) {
+            AxisZone::Min => "min",
+            AxisZone::Default => "default",
+            AxisZone::Max => "max",
+        }
+    }
+
+    #[test]
+    fn symmetric_thresholds() {
+        let limits = limits();
+        let thresholds = AxisThresholds::new(0.5, 0.5);
+
+        assert_eq!(zone(&limits, 0, thresholds), "default");
+        assert_eq!(zone(&limits, -4_999, thresholds), "default");
+        assert_eq!(zone(&limits, -5_000, thresholds), "min");
+        assert_eq!(zone(&limits, 4_999, thresholds), "default");
+        assert_eq!(zone(&limits, 5_000, thresholds), "max");
+    }
+
+    #[test]
+    fn asymmetric_thresholds() {
+        let limits = limits();
+        let thresholds = AxisThresholds::new(0.3, 0.7);
+
+        assert_eq!(zone(&limits, -2_999, thresholds), "default");
+        assert_eq!(zone(&limits, -3_000, thresholds), "min");
+        assert_eq!(zone(&limits, 3_000, thresholds), "default");
+        assert_eq!(zone(&limits, 6_999, thresholds), "default");
+        assert_eq!(zone(&limits, 7_000, thresholds), "max");
+    }
+
+    #[test]
+    fn thresholds_relative_to_default() {
+        let mut limits = AxisLimits::new(-32_768);
+        limits.extend(32_767);
+        let thresholds = AxisThresholds::new(0.25, 0.25);
+
+        assert_eq!(zone(&limits, -32_768, thresholds), "default");
+        assert_eq!(zone(&limits, -16_386, thresholds), "default");
+        assert_eq!(zone(&limits, -16_385, thresholds), "max");
+
+        let mut limits = AxisLimits::new(1_000);
+        limits.extend(-9_000);
+        limits.extend(3_000);
+
+        assert_eq!(zone(&limits, -1_499, thresholds), "default");
+        assert_eq!(zone(&limits, -1_500, thresholds), "min");
+        assert_eq!(zone(&limits, 1_499, thresholds), "default");
+        assert_eq!(zone(&limits, 1_500, thresholds), "max");
+    }
+
+    #[test]
+    fn overrides_replace_defined_directions() {
+        let thresholds = AxisThresholds::new(0.25, 0.25);
+
+        assert_eq!(
+            thresholds.with(Some(0.3), None),
+            AxisThresholds::new(0.3, 0.25)
+        );
+        assert_eq!(
+            thresholds.with(None, Some(0.7)),
+            AxisThresholds::new(0.25, 0.7)
+        );
+        assert_eq!(thresholds.with(None, None), thresholds);
+    }
+
+    fn limits_path(test: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "show-gamepad-limits-{}-{}.yaml",
+            test,
+            std::process::id()
+        ))
+    }
+
+    #[test]
+    fn saved_limits_of_other_controllers_kept() {
+        let path = limits_path("other_controllers_kept");
+        let mut first = joystick_limits();
+        first.update("0300", 0, 100);
+        first.save(&path).unwrap();
+
+        let mut second = joystick_limits();
+        second.load(&path, Some("0500")).unwrap();
+        second.update("0500", 0, 200);
+        second.save(&path).unwrap();
+
+        let mut loaded = joystick_limits();
+        loaded.load(&path, None).unwrap();
+
+        assert_eq!(loaded.limits("0300").len(), 1);
+        assert_eq!(loaded.limits("0500").len(), 1);
+
+        second.forget(&path).unwrap();
+        let mut loaded = joystick_limits();
+        loaded.load(&path, None).unwrap();
+
+        assert_eq!(loaded.limits("0300").len(), 1);
+        assert!(loaded.limits("0500").is_empty());
+
+        first.forget(&path).unwrap();
+
+        assert!(!path.exists());
+    }
+
+    fn joystick_limits() -> JoustickLimits {
+        JoustickLimits {
+            limits: HashMap::new(),
+            pinned: HashSet::new(),
+            deadzone: 0.25,
+            axis_deadzones: HashMap::new(),
+            guid_deadzones: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn learned_limits_saved_without_pinned() {
+        let path = limits_path("saved_without_pinned");
+        let mut limits = joystick_limits();
+        limits.update("0300", 0, 100);
+        limits.update("0300", 0, -200);
+        limits.pin("0300", &PinnedAxis::new(1, 0, -10, 10));
+        limits.save(&path).unwrap();
+
+        let mut loaded = joystick_limits();
+        loaded.load(&path, None).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let limits = loaded.limits("0300");
+
+        assert_eq!(limits.len(), 1);
+        assert_eq!(
+            (
+                limits[0].axis(),
+                limits[0].default(),
+                limits[0].min(),
+                limits[0].max()
+            ),
+            (0, 100, -200, 100)
+        );
+    }
+
+    #[test]
+    fn smoothing_stabilizes_noisy_zone() {
+        let limits = limits();
+        let thresholds = AxisThresholds::new(0.25, 0.25);
+        let noisy: Vec<i16> = (0..50)
+            .map(|index| match index % 2 {
+                0 => 2_000,
+                _ => 6_000,
+            })
+            .collect();
+
+        assert!(noisy
+            .iter()
+            .any(|&value| zone(&limits, value, thresholds) == "default"));
+
+        let mut smoothing = AxisSmoothing::new(0.2);
+        smoothing.filter("0300", 0, 4_000);
+
+        for value in noisy {
+            let value = smoothing.filter("0300", 0, value);
+
+            assert_eq!(zone(&limits, value, thresholds), "max");
+        }
+    }
+
+    #[test]
+    fn identical_devices_smoothed_separately() {
+        let mut smoothing = AxisSmoothing::new(0.5);
+        smoothing.filter("0300", 0, 0);
+        smoothing.filter("0300#2", 0, 0);
+
+        assert_eq!(smoothing.filter("0300#2", 0, 10_000), 5_000);
+        assert_eq!(smoothing.filter("0300", 0, 0), 0);
+
+        smoothing.remove("0300#2");
+
+        assert_eq!(smoothing.filter("0300#2", 0, 10_000), 10_000);
+    }
+
+    #[test]
+    fn bands_pressed_only_when_deflected() {
+        let bands: Vec<AxisBand> =
+            serde_yaml::from_str("[{ axis: 2, lo: 0.3, hi: 0.7 }, { axis: 3, lo: 0.0, hi: 1.0 }]")
+                .unwrap();
+        let band = Input::axis_band(2, 30, 70);
+
+        assert!(band_inputs(&bands, 2, 0.5, &AxisZone::Default).is_empty());
+        assert_eq!(
+            band_inputs(&bands, 2, 0.65, &AxisZone::Max),
+            vec![band.clone()]
+        );
+        assert_eq!(band_inputs(&bands, 2, 0.3, &AxisZone::Min), vec![band]);
+        assert!(band_inputs(&bands, 2, 0.8, &AxisZone::Max).is_empty());
+        assert!(band_inputs(&bands, 1, 0.5, &AxisZone::Max).is_empty());
+    }
+
+    #[test]
+    fn device_guid_strips_ordinal() {
+        assert_eq!(device_guid("0300"), "0300");
+        assert_eq!(device_guid("0300#2"), "0300");
+    }
+}
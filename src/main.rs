@@ -1,126 +1,585 @@
 #![windows_subsystem = "windows"]
 
+mod blend;
 mod config;
 mod error;
 mod font;
+mod foreground;
 mod joysticks;
 mod mapping;
 mod options;
+mod osc;
+mod preferences;
+mod primitive;
+mod remap;
+mod remote;
+mod sound;
+mod strings;
+mod timeline;
 mod visualizer;
 
+use std::path::Path;
 use std::path::PathBuf;
 use std::thread;
 use std::time::Duration;
+use std::time::Instant;
 
 use error::ApplicationResult;
 use font::Font;
 use options::Options;
+use osc::Osc;
+use preferences::Preferences;
 use sdl2::event::Event;
+use sdl2::event::WindowEvent;
 use sdl2::filesystem;
 use sdl2::image::LoadSurface;
+use sdl2::image::LoadTexture;
 use sdl2::keyboard::Keycode;
+use sdl2::keyboard::Mod;
 use sdl2::messagebox;
 use sdl2::messagebox::ButtonData;
 use sdl2::messagebox::MessageBoxButtonFlag;
 use sdl2::messagebox::MessageBoxFlag;
+use sdl2::pixels::Color;
+use sdl2::render::WindowCanvas;
 use sdl2::surface::Surface;
+use sdl2::sys::SDL_WindowFlags;
+use sdl2::Sdl;
+use sound::Sounds;
 use structopt::StructOpt;
 use visualizer::Visualiser;
 
 const FRAME_TIME: Duration = Duration::from_millis(1_000 / 60);
+const MIN_FRAME_TIME: Duration = Duration::from_millis(4);
+const FOREGROUND_TIME: Duration = Duration::from_millis(500);
+const TITLE: &str = "Show Controller";
 
-fn run() -> ApplicationResult<()> {
+fn run(options: &Options) -> ApplicationResult<()> {
     sdl2::hint::set("SDL_JOYSTICK_ALLOW_BACKGROUND_EVENTS", "1");
 
-    let options = Options::from_args();
-    let config = config::load(options.config_path())?;
+    if options.minimize_on_close() {
+        sdl2::hint::set("SDL_QUIT_ON_LAST_WINDOW_CLOSE", "0");
+    }
+
+    if options.windows() == 0 {
+        return Err("At least one window required".to_string().into());
+    }
+
+    let mut config = config::load(options.config_path())?;
+    let preferences_dir = filesystem::pref_path("snake", "show-controller")?;
+    let preferences_dir = PathBuf::from(preferences_dir);
+    config.resolve_urls(&preferences_dir.join("cache"))?;
+
+    if config.foreground_title().is_some() {
+        sdl2::hint::set("SDL_WINDOW_NO_ACTIVATION_WHEN_SHOWN", "1");
+    }
+
     let sdl = sdl2::init()?;
     let video_subsystem = sdl.video()?;
     let joystick_subsystem = sdl.joystick()?;
-    let (width, height) = Surface::from_file(config.background())?.size();
-    let window = video_subsystem
-        .window("Show Controller", width, height)
-        .position_centered()
-        .build()?;
+    let game_controller_subsystem = sdl.game_controller()?;
+
+    let mut warnings = Vec::new();
+    let mut messages = Vec::new();
+
+    if let Some(path) = options.controller_db() {
+        match game_controller_subsystem.load_mappings(path) {
+            Ok(count) => messages.push(config.strings().controller_db_loaded(count)),
+            Err(error) => warnings.push(config.strings().controller_db_error(&error.to_string())),
+        }
+    }
+
+    let (width, height) = match (config.size(), config.background()) {
+        (Some(size), _) => size,
+        (None, Some(background)) => Surface::from_file(background)?.size(),
+        (None, None) => return Err("Window size not defined".to_string().into()),
+    };
+    let display = match options.display() {
+        Some(display) => {
+            let displays = video_subsystem.num_video_displays()?;
+
+            if display < 0 || display >= displays {
+                return Err(format!(
+                    "Display {} not found, {} displays connected",
+                    display, displays
+                )
+                .into());
+            }
+
+            Some(video_subsystem.display_bounds(display)?)
+        }
+        None => None,
+    };
+    let mut canvases = Vec::new();
+
+    for index in 0..options.windows() {
+        let title = match options.windows() {
+            1 => TITLE.to_string(),
+            _ => format!("{} {}", TITLE, index + 1),
+        };
+        let mut window = video_subsystem.window(&title, width, height);
+
+        match display {
+            Some(bounds) => window.position(
+                bounds.x() + (bounds.width() as i32 - width as i32) / 2,
+                bounds.y() + (bounds.height() as i32 - height as i32) / 2,
+            ),
+            None => window.position_centered(),
+        };
+
+        if options.fullscreen() {
+            window.fullscreen_desktop();
+        }
+
+        let mut canvas = window.build()?.into_canvas().accelerated().build()?;
+
+        if options.fullscreen() {
+            canvas.set_logical_size(width, height)?;
+        }
+
+        canvases.push(canvas);
+    }
+
+    let cycle_key = Keycode::from_name(config.cycle_key())
+        .ok_or_else(|| format!("Unknown cycle key {}", config.cycle_key()))?;
     let mut event_pump = sdl.event_pump()?;
-    let mut canvas = window.into_canvas().accelerated().build()?;
-    let texture_creator = canvas.texture_creator();
-    let preferences = filesystem::pref_path("snake", "show-controller")?;
-    let mut preferences = PathBuf::from(preferences);
-    preferences.push("preferences.yaml");
-
-    let font = Font::create(16, 32, &texture_creator)?;
-    let mut visualiser = Visualiser::create(
-        &config,
-        preferences,
-        &font,
-        &texture_creator,
-        &joystick_subsystem,
-    )?;
+    let mouse = sdl.mouse();
+    let window_ids: Vec<_> = canvases.iter().map(|canvas| canvas.window().id()).collect();
+    let texture_creators: Vec<_> = canvases
+        .iter()
+        .map(|canvas| canvas.texture_creator())
+        .collect();
+    let preferences = Preferences::load(preferences_dir.join("preferences.yaml"), &config)?;
+
+    let mut fonts = Vec::new();
+
+    for texture_creator in &texture_creators {
+        fonts.push(Font::create(16, 32, texture_creator)?);
+    }
+
+    let sounds = match options.sound() {
+        true => Sounds::open(&config).unwrap_or_else(|error| {
+            warnings.push(config.strings().sounds_error(&error.to_string()));
+
+            Sounds::empty()
+        }),
+        false => Sounds::empty(),
+    };
+    let osc = match config.osc() {
+        Some(target) => Osc::open(target).unwrap_or_else(|error| {
+            warnings.push(config.strings().osc_error(&error.to_string()));
+
+            Osc::empty()
+        }),
+        None => Osc::empty(),
+    };
+    let mut visualisers = Vec::new();
+
+    for warning in &warnings {
+        eprintln!("Warning: {}", warning);
+    }
+
+    for (index, (font, texture_creator)) in fonts.iter().zip(&texture_creators).enumerate() {
+        let mut visualiser = Visualiser::create(
+            &config,
+            &preferences,
+            index,
+            options.device(index),
+            font,
+            &sounds,
+            &osc,
+            texture_creator,
+            &joystick_subsystem,
+        )?;
+
+        for message in warnings.iter().chain(&messages) {
+            visualiser.warn(message.clone());
+        }
+
+        if options.reset_help() {
+            visualiser.reset_help();
+        }
+
+        if options.watch() {
+            visualiser.watch_preferences();
+        }
+
+        if let Some(path) = options.timeline() {
+            visualiser.record_timeline(&timeline_path(path, index))?;
+        }
+
+        visualisers.push(visualiser);
+    }
+
+    let hide = config.hide_input().is_some();
+
+    if let Some(splash) = config.splash() {
+        let mut textures = Vec::new();
+
+        for texture_creator in &texture_creators {
+            textures.push(texture_creator.load_texture(splash.path())?);
+        }
+
+        let start = Instant::now();
+
+        'splash: while start.elapsed() < splash.duration() {
+            for event in event_pump.poll_iter() {
+                match event {
+                    Event::Window {
+                        window_id,
+                        win_event: WindowEvent::Close,
+                        ..
+                    } if options.minimize_on_close() && !exit_held(&sdl) => {
+                        minimize(&mut canvases, &window_ids, window_id, hide)
+                    }
+                    Event::Quit { .. }
+                    | Event::Window {
+                        win_event: WindowEvent::Close,
+                        ..
+                    } => return Ok(()),
+                    Event::KeyDown { .. }
+                    | Event::JoyButtonDown { .. }
+                    | Event::MouseButtonDown { .. } => break 'splash,
+                    Event::JoyDeviceAdded { which, .. } => {
+                        for visualiser in &mut visualisers {
+                            visualiser.joystick_add(&joystick_subsystem, which)?
+                        }
+                    }
+                    Event::JoyDeviceRemoved { which, .. } => {
+                        for visualiser in &mut visualisers {
+                            visualiser.joystick_remove(which)
+                        }
+                    }
+                    _ => {}
+                }
+            }
+
+            for (texture, canvas) in textures.iter().zip(&mut canvases) {
+                canvas.set_draw_color(Color::RGB(0, 0, 0));
+                canvas.clear();
+                canvas.copy(texture, None, None)?;
+                canvas.present();
+            }
+
+            thread::sleep(FRAME_TIME);
+        }
+    }
+
+    let mut foreground_checked = Instant::now();
+    let mut windows_shown = true;
 
     'running: loop {
+        let frame_start = Instant::now();
+
         for event in event_pump.poll_iter() {
             match event {
+                Event::Window {
+                    window_id,
+                    win_event: WindowEvent::Close,
+                    ..
+                } if options.minimize_on_close() && !exit_held(&sdl) => {
+                    minimize(&mut canvases, &window_ids, window_id, hide)
+                }
                 Event::Quit { .. }
                 | Event::KeyDown {
                     keycode: Some(Keycode::Escape),
                     ..
+                }
+                | Event::Window {
+                    win_event: WindowEvent::Close,
+                    ..
                 } => break 'running,
                 Event::KeyDown {
+                    window_id,
                     keycode: Some(Keycode::F1),
                     ..
-                } => visualiser.update_setup()?,
+                } => {
+                    if let Some(visualiser) = focused(&mut visualisers, &window_ids, window_id) {
+                        visualiser.update_setup()?
+                    }
+                }
                 Event::KeyDown {
+                    window_id,
                     keycode: Some(Keycode::F2),
                     ..
-                } => visualiser.cancel_setup(),
+                } => {
+                    if let Some(visualiser) = focused(&mut visualisers, &window_ids, window_id) {
+                        visualiser.cancel_setup()
+                    }
+                }
                 Event::KeyDown {
+                    window_id,
                     keycode: Some(Keycode::F3),
                     ..
-                } => visualiser.reset_limits(),
+                } => {
+                    if let Some(visualiser) = focused(&mut visualisers, &window_ids, window_id) {
+                        visualiser.reset_limits()
+                    }
+                }
                 Event::KeyDown {
+                    window_id,
+                    keycode: Some(Keycode::F4),
+                    ..
+                } => {
+                    if let Some(visualiser) = focused(&mut visualisers, &window_ids, window_id) {
+                        visualiser.toggle_diagnostics()
+                    }
+                }
+                Event::KeyDown {
+                    window_id,
+                    keycode: Some(Keycode::F5),
+                    ..
+                } => {
+                    if let Some(visualiser) = focused(&mut visualisers, &window_ids, window_id) {
+                        visualiser.pin_limits()?
+                    }
+                }
+                Event::KeyDown {
+                    window_id,
+                    keycode: Some(Keycode::F6),
+                    ..
+                } => {
+                    if let Some(visualiser) = focused(&mut visualisers, &window_ids, window_id) {
+                        visualiser.toggle_fps()
+                    }
+                }
+                Event::KeyDown {
+                    window_id,
+                    keycode: Some(Keycode::F8),
+                    ..
+                } => {
+                    if let Some(visualiser) = focused(&mut visualisers, &window_ids, window_id) {
+                        visualiser.calibrate()?
+                    }
+                }
+                Event::KeyDown {
+                    window_id,
+                    keycode: Some(Keycode::F9),
+                    ..
+                } => {
+                    if let Some(visualiser) = focused(&mut visualisers, &window_ids, window_id) {
+                        visualiser.reset_counters()
+                    }
+                }
+                Event::KeyDown {
+                    window_id,
+                    keycode: Some(Keycode::F10),
+                    ..
+                } => {
+                    if let Some(visualiser) = focused(&mut visualisers, &window_ids, window_id) {
+                        visualiser.toggle_heatmap()
+                    }
+                }
+                Event::KeyDown {
+                    window_id,
+                    keycode: Some(keycode),
+                    ..
+                } if keycode == cycle_key => {
+                    if let Some(visualiser) = focused(&mut visualisers, &window_ids, window_id) {
+                        visualiser.cycle_controller()
+                    }
+                }
+                Event::KeyDown {
+                    window_id,
                     scancode: Some(scancode),
                     ..
-                } => visualiser.key_down(scancode.name()),
+                } => {
+                    if let Some(visualiser) = focused(&mut visualisers, &window_ids, window_id) {
+                        visualiser.key_down(scancode.name())
+                    }
+                }
                 Event::KeyUp {
+                    window_id,
                     scancode: Some(scancode),
                     ..
-                } => visualiser.key_up(scancode.name()),
+                } => {
+                    if let Some(visualiser) = focused(&mut visualisers, &window_ids, window_id) {
+                        visualiser.key_up(scancode.name())
+                    }
+                }
+                Event::Window {
+                    win_event: WindowEvent::Enter,
+                    ..
+                } if options.hide_cursor() => mouse.show_cursor(false),
+                Event::Window {
+                    win_event: WindowEvent::Leave | WindowEvent::FocusLost,
+                    ..
+                } if options.hide_cursor() => mouse.show_cursor(true),
                 Event::JoyDeviceAdded { which, .. } => {
-                    visualiser.joystick_add(&joystick_subsystem, which)?
+                    for visualiser in &mut visualisers {
+                        visualiser.joystick_add(&joystick_subsystem, which)?
+                    }
+                }
+                Event::JoyDeviceRemoved { which, .. } => {
+                    for visualiser in &mut visualisers {
+                        visualiser.joystick_remove(which)
+                    }
                 }
-                Event::JoyDeviceRemoved { which, .. } => visualiser.joystick_remove(which),
                 _ => {}
             }
         }
 
-        visualiser.update()?;
-        visualiser.draw(&mut canvas)?;
-        canvas.present();
+        if let Some(pattern) = config.foreground_title() {
+            if foreground_checked.elapsed() >= FOREGROUND_TIME {
+                foreground_checked = Instant::now();
+
+                if let Some(title) = foreground::title() {
+                    let shown = title.contains(pattern) || title.starts_with(TITLE);
+
+                    if shown != windows_shown {
+                        windows_shown = shown;
+
+                        for canvas in &mut canvases {
+                            match shown {
+                                true => canvas.window_mut().show(),
+                                false => canvas.window_mut().hide(),
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        for (visualiser, canvas) in visualisers.iter_mut().zip(&mut canvases) {
+            visualiser.update()?;
+
+            if visualiser.hide_pressed() {
+                toggle_hidden(canvas);
+            }
+
+            visualiser.draw(canvas)?;
+            canvas.present();
+        }
+
+        if options.wait_events() {
+            wait_event(FRAME_TIME.saturating_sub(frame_start.elapsed()));
+            thread::sleep(MIN_FRAME_TIME.saturating_sub(frame_start.elapsed()));
+        } else {
+            match options.poll_time() {
+                Some(poll_time) => {
+                    let sleep_start = Instant::now();
+
+                    while sleep_start.elapsed() + poll_time < FRAME_TIME {
+                        thread::sleep(poll_time);
+                        joystick_subsystem.update();
+
+                        for visualiser in &mut visualisers {
+                            visualiser.poll()?;
+                        }
+                    }
+
+                    thread::sleep(FRAME_TIME.saturating_sub(sleep_start.elapsed()));
+                }
+                None => thread::sleep(FRAME_TIME),
+            }
+        }
+    }
 
-        thread::sleep(FRAME_TIME);
+    for visualiser in &mut visualisers {
+        visualiser.close()?;
     }
 
     Ok(())
 }
 
+/// Returns timeline path of window, windows after first add window number to
+/// file name.
+fn timeline_path(path: &Path, index: usize) -> PathBuf {
+    match (index, path.file_stem(), path.extension()) {
+        (0, _, _) => path.to_path_buf(),
+        (_, Some(stem), Some(extension)) => path.with_file_name(format!(
+            "{}-{}.{}",
+            stem.to_string_lossy(),
+            index + 1,
+            extension.to_string_lossy()
+        )),
+        _ => path.with_file_name(format!(
+            "{}-{}",
+            path.file_name().unwrap_or_default().to_string_lossy(),
+            index + 1
+        )),
+    }
+}
+
+/// Waits until any event received or timeout elapsed. Event is kept in queue.
+fn wait_event(timeout: Duration) {
+    // Event pump can wait only removing event from queue.
+    unsafe {
+        sdl2::sys::SDL_WaitEventTimeout(std::ptr::null_mut(), timeout.as_millis() as i32);
+    }
+}
+
+/// Checks that Shift held, closing window with Shift exits application even
+/// when windows minimized on close.
+fn exit_held(sdl: &Sdl) -> bool {
+    sdl.keyboard()
+        .mod_state()
+        .intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD)
+}
+
+/// Minimizes closed window, or hides it when it can be shown again with hide
+/// input.
+fn minimize(canvases: &mut [WindowCanvas], window_ids: &[u32], window_id: u32, hide: bool) {
+    if let Some(index) = window_ids.iter().position(|&id| id == window_id) {
+        match hide {
+            true => canvases[index].window_mut().hide(),
+            false => canvases[index].window_mut().minimize(),
+        }
+    }
+}
+
+/// Shows hidden window or hides shown window.
+fn toggle_hidden(canvas: &mut WindowCanvas) {
+    let hidden = canvas.window().window_flags() & SDL_WindowFlags::SDL_WINDOW_HIDDEN as u32 != 0;
+
+    match hidden {
+        true => canvas.window_mut().show(),
+        false => canvas.window_mut().hide(),
+    }
+}
+
+fn focused<'a, 'b>(
+    visualisers: &'a mut [Visualiser<'b>],
+    window_ids: &[u32],
+    window_id: u32,
+) -> Option<&'a mut Visualiser<'b>> {
+    window_ids
+        .iter()
+        .position(|&id| id == window_id)
+        .and_then(move |index| visualisers.get_mut(index))
+}
+
 fn main() {
-    if let Err(error) = run() {
-        let flags = MessageBoxButtonFlag::empty()
-            .union(MessageBoxButtonFlag::RETURNKEY_DEFAULT)
-            .union(MessageBoxButtonFlag::ESCAPEKEY_DEFAULT);
-        let button = ButtonData {
-            flags,
-            button_id: 1,
-            text: "Ok",
-        };
-        let _ = messagebox::show_message_box(
-            MessageBoxFlag::empty(),
-            &[button],
-            "Error",
-            &error.to_string(),
-            None,
-            None,
-        );
+    let options = Options::from_args();
+
+    if let Err(error) = run(&options) {
+        if options.no_gui_errors() || !show_error(&error.to_string()) {
+            eprintln!("Error: {}", error);
+        }
+
+        std::process::exit(1);
     }
 }
+
+/// Shows error message box, returns false if message box can not be shown,
+/// e.g. when no display available.
+fn show_error(message: &str) -> bool {
+    let flags = MessageBoxButtonFlag::empty()
+        .union(MessageBoxButtonFlag::RETURNKEY_DEFAULT)
+        .union(MessageBoxButtonFlag::ESCAPEKEY_DEFAULT);
+    let button = ButtonData {
+        flags,
+        button_id: 1,
+        text: "Ok",
+    };
+
+    messagebox::show_message_box(
+        MessageBoxFlag::empty(),
+        &[button],
+        "Error",
+        message,
+        None,
+        None,
+    )
+    .is_ok()
+}
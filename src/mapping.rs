@@ -1,5 +1,7 @@
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::fs;
 use std::fs::File;
 use std::path::Path;
 
@@ -75,6 +77,7 @@ pub enum Input {
     Button { button: u32 },
     Axis { axis: u32, direction: Direction },
     Hat { hat: u32, state: State },
+    AxisBand { axis: u32, lo: u8, hi: u8 },
 }
 
 impl Input {
@@ -100,6 +103,12 @@ impl Input {
         }
     }
 
+    /// Creates input for axis position within band, band bounds are
+    /// percents of axis range.
+    pub fn axis_band(axis: u32, lo: u8, hi: u8) -> Self {
+        Input::AxisBand { axis, lo, hi }
+    }
+
     pub fn hat<S>(hat: u32, state: S) -> Self
     where
         S: Into<State>,
@@ -118,6 +127,7 @@ impl ToString for Input {
             Input::Button { button } => format!("b{}", button),
             Input::Axis { axis, direction } => format!("a{} {}", axis, direction.as_str()),
             Input::Hat { hat, state } => format!("h{} {}", hat, state.as_str()),
+            Input::AxisBand { axis, lo, hi } => format!("a{} {}-{}%", axis, lo, hi),
         }
     }
 }
@@ -125,15 +135,77 @@ impl ToString for Input {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Mapping {
     joysticks: HashMap<String, Vec<SpriteMapping>>,
+    #[serde(default)]
+    help_dismissed: bool,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    limits: HashMap<String, Vec<PinnedAxis>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    selected: BTreeMap<usize, String>,
+    #[serde(skip)]
+    exact: HashSet<usize>,
+    #[serde(skip)]
+    pressed_limits: HashMap<usize, (Option<usize>, Option<usize>)>,
 }
 
 impl Mapping {
     pub fn new() -> Self {
         Self {
             joysticks: HashMap::new(),
+            help_dismissed: false,
+            limits: HashMap::new(),
+            selected: BTreeMap::new(),
+            exact: HashSet::new(),
+            pressed_limits: HashMap::new(),
         }
     }
 
+    /// Replaces saved state with state of loaded mapping. Sprite match
+    /// settings are kept.
+    pub fn replace(&mut self, loaded: Mapping) {
+        self.joysticks = loaded.joysticks;
+        self.help_dismissed = loaded.help_dismissed;
+        self.limits = loaded.limits;
+        self.selected = loaded.selected;
+    }
+
+    pub fn help_dismissed(&self) -> bool {
+        self.help_dismissed
+    }
+
+    pub fn set_help_dismissed(&mut self, help_dismissed: bool) {
+        self.help_dismissed = help_dismissed;
+    }
+
+    /// Returns identity of controller selected in window in last session.
+    pub fn selected(&self, window: usize) -> Option<&String> {
+        self.selected.get(&window)
+    }
+
+    pub fn set_selected(&mut self, window: usize, selected: Option<String>) {
+        match selected {
+            Some(selected) => self.selected.insert(window, selected),
+            None => self.selected.remove(&window),
+        };
+    }
+
+    /// Returns pinned axis limits of all controllers.
+    pub fn limits(&self) -> &HashMap<String, Vec<PinnedAxis>> {
+        &self.limits
+    }
+
+    pub fn set_limits(&mut self, guid: &str, limits: Vec<PinnedAxis>) {
+        self.limits.insert(guid.into(), limits);
+    }
+
+    /// Sets sprites which match only when pressed inputs exactly equal to
+    /// their combination, other sprites match when combination pressed.
+    pub fn set_exact(&mut self, exact: HashSet<usize>) {
+        self.exact = exact;
+    }
+
+    /// Binds pressed inputs to sprite, replacing previous binding of this
+    /// sprite. Empty pressed set removes binding of this sprite only, mappings
+    /// of other sprites are kept.
     pub fn push(&mut self, guid: &str, pressed: &HashSet<Input>, sprite: usize) {
         let entry = self.joysticks.entry(guid.into()).or_insert_with(Vec::new);
         entry.retain(|sm| sm.sprite() != sprite);
@@ -142,16 +214,56 @@ impl Mapping {
             let sprite_mapping = SpriteMapping::new(pressed.clone(), sprite);
 
             entry.push(sprite_mapping);
-            entry.sort_by_key(|sm| (-(sm.buttons.len() as isize), sm.sprite()));
+            entry.sort_by_key(|sm| (-(sm.required() as isize), sm.sprite()));
+        }
+    }
+
+    /// Sets minimal and maximal number of pressed inputs for sprites, sprite
+    /// does not match when number of pressed inputs out of limits.
+    pub fn set_pressed_limits(&mut self, limits: HashMap<usize, (Option<usize>, Option<usize>)>) {
+        self.pressed_limits = limits;
+    }
+
+    /// Checks that number of pressed inputs within sprite limits.
+    fn pressed_within(&self, sprite: usize, pressed: usize) -> bool {
+        match self.pressed_limits.get(&sprite) {
+            Some(&(min, max)) => {
+                min.is_none_or(|min| pressed >= min) && max.is_none_or(|max| pressed <= max)
+            }
+            None => true,
         }
     }
 
+    /// Returns sprites which mappings pressed. Mapping pressed when all its
+    /// buttons pressed (other inputs may be pressed too) and at least one input
+    /// of every `any` group pressed. Exact mapping also requires that no other
+    /// inputs pressed.
     pub fn sprites(&self, giud: &str, pressed: &HashSet<Input>) -> Vec<usize> {
         let mut result = Vec::new();
 
         if let Some(list) = self.joysticks.get(giud) {
             for sprite_mapping in list {
-                if sprite_mapping.buttons().is_subset(pressed) {
+                let matched = sprite_mapping.matches(pressed)
+                    && (!self.exact.contains(&sprite_mapping.sprite())
+                        || pressed.iter().all(|input| sprite_mapping.contains(input)));
+
+                if matched && self.pressed_within(sprite_mapping.sprite(), pressed.len()) {
+                    result.push(sprite_mapping.sprite());
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Returns sprites which combinations pressed partially, when some but not
+    /// all combination inputs are pressed.
+    pub fn partial(&self, giud: &str, pressed: &HashSet<Input>) -> Vec<usize> {
+        let mut result = Vec::new();
+
+        if let Some(list) = self.joysticks.get(giud) {
+            for sprite_mapping in list {
+                if !sprite_mapping.matches(pressed) && sprite_mapping.touches(pressed) {
                     result.push(sprite_mapping.sprite());
                 }
             }
@@ -160,6 +272,32 @@ impl Mapping {
         result
     }
 
+    /// Returns trace for every sprite mapping of controller with inputs
+    /// missing to match this mapping, or `None` if controller has no mapping.
+    /// All inputs of `any` group without pressed inputs are reported missing.
+    pub fn trace(&self, giud: &str, pressed: &HashSet<Input>) -> Option<Vec<SpriteTrace>> {
+        self.joysticks.get(giud).map(|list| {
+            list.iter()
+                .map(|sprite_mapping| SpriteTrace {
+                    sprite: sprite_mapping.sprite(),
+                    touched: sprite_mapping.touches(pressed),
+                    missing: sprite_mapping
+                        .buttons()
+                        .difference(pressed)
+                        .chain(
+                            sprite_mapping
+                                .any()
+                                .iter()
+                                .filter(|group| group.is_disjoint(pressed))
+                                .flatten(),
+                        )
+                        .cloned()
+                        .collect(),
+                })
+                .collect()
+        })
+    }
+
     pub fn load<P>(path: P) -> ApplicationResult<Self>
     where
         P: AsRef<Path>,
@@ -179,24 +317,375 @@ impl Mapping {
 
         Ok(())
     }
+
+    /// Saves human readable list of sprite inputs and pinned limits. Text file
+    /// is never loaded, preferences file is the only source of mapping.
+    pub fn save_text<P, F>(&self, path: P, name: F) -> ApplicationResult<()>
+    where
+        P: AsRef<Path>,
+        F: Fn(usize) -> Option<String>,
+    {
+        fs::write(path, self.to_text(name))?;
+
+        Ok(())
+    }
+
+    /// Formats mapping as text: one line per sprite with sorted inputs, `any`
+    /// groups in parentheses. Controllers sorted by GUID.
+    fn to_text<F>(&self, name: F) -> String
+    where
+        F: Fn(usize) -> Option<String>,
+    {
+        let mut guids: Vec<_> = self.joysticks.keys().chain(self.limits.keys()).collect();
+        guids.sort();
+        guids.dedup();
+
+        let mut text = String::new();
+
+        for guid in guids {
+            text.push_str(&format!("{}:\n", guid));
+
+            for sprite_mapping in self.joysticks.get(guid).into_iter().flatten() {
+                let mut inputs = vec![sorted_inputs(sprite_mapping.buttons(), ", ")];
+                inputs.retain(|inputs| !inputs.is_empty());
+
+                for group in sprite_mapping.any() {
+                    inputs.push(format!("any ({})", sorted_inputs(group, " | ")));
+                }
+
+                let sprite = sprite_mapping.sprite();
+                let name = name(sprite).unwrap_or_else(|| "Unknown".into());
+
+                text.push_str(&format!("  {} ({}): {}\n", name, sprite, inputs.join(", ")));
+            }
+
+            for pinned in self.limits.get(guid).into_iter().flatten() {
+                text.push_str(&format!(
+                    "  limits a{}: default {}, min {}, max {}\n",
+                    pinned.axis(),
+                    pinned.default(),
+                    pinned.min(),
+                    pinned.max()
+                ));
+            }
+        }
+
+        text
+    }
+}
+
+fn sorted_inputs(inputs: &HashSet<Input>, separator: &str) -> String {
+    let mut inputs: Vec<_> = inputs.iter().map(ToString::to_string).collect();
+    inputs.sort();
+
+    inputs.join(separator)
 }
 
+/// Inputs bound to sprite. All buttons must be pressed, every `any` group
+/// requires at least one of its inputs pressed.
 #[derive(Debug, Serialize, Deserialize)]
 struct SpriteMapping {
     buttons: HashSet<Input>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    any: Vec<HashSet<Input>>,
     sprite: usize,
 }
 
 impl SpriteMapping {
     pub fn new(buttons: HashSet<Input>, sprite: usize) -> Self {
-        Self { buttons, sprite }
+        Self {
+            buttons,
+            any: Vec::new(),
+            sprite,
+        }
     }
 
     pub fn buttons(&self) -> &HashSet<Input> {
         &self.buttons
     }
 
+    pub fn any(&self) -> &[HashSet<Input>] {
+        &self.any
+    }
+
+    pub fn sprite(&self) -> usize {
+        self.sprite
+    }
+
+    /// Returns number of inputs required to match mapping.
+    fn required(&self) -> usize {
+        self.buttons.len() + self.any.len()
+    }
+
+    fn contains(&self, input: &Input) -> bool {
+        self.buttons.contains(input) || self.any.iter().any(|group| group.contains(input))
+    }
+
+    fn matches(&self, pressed: &HashSet<Input>) -> bool {
+        self.buttons.is_subset(pressed) && self.any.iter().all(|group| !group.is_disjoint(pressed))
+    }
+
+    fn touches(&self, pressed: &HashSet<Input>) -> bool {
+        pressed.iter().any(|input| self.contains(input))
+    }
+}
+
+/// Fixed axis limits, used instead of limits learned at runtime.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PinnedAxis {
+    axis: u32,
+    default: i32,
+    min: i32,
+    max: i32,
+}
+
+impl PinnedAxis {
+    pub fn new(axis: u32, default: i32, min: i32, max: i32) -> Self {
+        Self {
+            axis,
+            default,
+            min,
+            max,
+        }
+    }
+
+    pub fn axis(&self) -> u32 {
+        self.axis
+    }
+
+    pub fn default(&self) -> i32 {
+        self.default
+    }
+
+    pub fn min(&self) -> i32 {
+        self.min
+    }
+
+    pub fn max(&self) -> i32 {
+        self.max
+    }
+}
+
+#[derive(Debug)]
+pub struct SpriteTrace {
+    sprite: usize,
+    touched: bool,
+    missing: Vec<Input>,
+}
+
+impl SpriteTrace {
     pub fn sprite(&self) -> usize {
         self.sprite
     }
+
+    pub fn matched(&self) -> bool {
+        self.missing.is_empty()
+    }
+
+    /// Checks that at least one input of mapping pressed.
+    pub fn touched(&self) -> bool {
+        self.touched
+    }
+
+    pub fn missing(&self) -> &[Input] {
+        &self.missing
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GUID: &str = "030000005e0400008e02000010010000";
+
+    fn inputs(buttons: &[u32]) -> HashSet<Input> {
+        buttons.iter().cloned().map(Input::button).collect()
+    }
+
+    fn sprites(mapping: &Mapping) -> Vec<(usize, usize)> {
+        mapping.joysticks[GUID]
+            .iter()
+            .map(|sm| (sm.sprite, sm.buttons.len()))
+            .collect()
+    }
+
+    #[test]
+    fn single_button_match() {
+        let mut mapping = Mapping::new();
+        mapping.push(GUID, &inputs(&[0]), 1);
+
+        assert_eq!(mapping.sprites(GUID, &inputs(&[0])), vec![1]);
+        assert!(mapping.sprites(GUID, &inputs(&[1])).is_empty());
+        assert!(mapping.sprites(GUID, &inputs(&[])).is_empty());
+        assert!(mapping.sprites("unknown", &inputs(&[0])).is_empty());
+    }
+
+    #[test]
+    fn combination_matches_superset() {
+        let mut mapping = Mapping::new();
+        mapping.push(GUID, &inputs(&[0, 1]), 1);
+
+        assert!(mapping.sprites(GUID, &inputs(&[0])).is_empty());
+        assert_eq!(mapping.sprites(GUID, &inputs(&[0, 1])), vec![1]);
+        assert_eq!(mapping.sprites(GUID, &inputs(&[0, 1, 2])), vec![1]);
+    }
+
+    #[test]
+    fn exact_matches_only_equal_inputs() {
+        let mut mapping = Mapping::new();
+        mapping.push(GUID, &inputs(&[0, 1]), 1);
+        mapping.push(GUID, &inputs(&[0]), 2);
+        mapping.set_exact([1].into_iter().collect());
+
+        assert_eq!(mapping.sprites(GUID, &inputs(&[0, 1])), vec![1, 2]);
+        assert_eq!(mapping.sprites(GUID, &inputs(&[0, 1, 2])), vec![2]);
+        assert!(mapping.sprites(GUID, &inputs(&[1])).is_empty());
+    }
+
+    #[test]
+    fn pressed_limits_bound_matching() {
+        let mut mapping = Mapping::new();
+        mapping.push(GUID, &inputs(&[0]), 1);
+        mapping.push(GUID, &inputs(&[0]), 2);
+        mapping.push(GUID, &inputs(&[0]), 3);
+        mapping.set_pressed_limits(
+            [
+                (1, (Some(2), Some(2))),
+                (2, (Some(2), None)),
+                (3, (None, Some(2))),
+            ]
+            .into_iter()
+            .collect(),
+        );
+
+        assert_eq!(mapping.sprites(GUID, &inputs(&[0])), vec![3]);
+        assert_eq!(mapping.sprites(GUID, &inputs(&[0, 1])), vec![1, 2, 3]);
+        assert_eq!(mapping.sprites(GUID, &inputs(&[0, 1, 2])), vec![2]);
+    }
+
+    fn push_any(mapping: &mut Mapping, buttons: &[u32], any: &[&[u32]], sprite: usize) {
+        let mut sprite_mapping = SpriteMapping::new(inputs(buttons), sprite);
+        sprite_mapping.any = any.iter().map(|group| inputs(group)).collect();
+
+        mapping
+            .joysticks
+            .entry(GUID.into())
+            .or_default()
+            .push(sprite_mapping);
+    }
+
+    #[test]
+    fn any_group_matches_either_input() {
+        let mut mapping = Mapping::new();
+        push_any(&mut mapping, &[], &[&[4, 5]], 1);
+
+        assert_eq!(mapping.sprites(GUID, &inputs(&[4])), vec![1]);
+        assert_eq!(mapping.sprites(GUID, &inputs(&[5])), vec![1]);
+        assert_eq!(mapping.sprites(GUID, &inputs(&[4, 5])), vec![1]);
+        assert!(mapping.sprites(GUID, &inputs(&[0])).is_empty());
+    }
+
+    #[test]
+    fn any_group_combines_with_buttons() {
+        let mut mapping = Mapping::new();
+        push_any(&mut mapping, &[0], &[&[4, 5]], 1);
+
+        assert!(mapping.sprites(GUID, &inputs(&[0])).is_empty());
+        assert!(mapping.sprites(GUID, &inputs(&[4])).is_empty());
+        assert_eq!(mapping.sprites(GUID, &inputs(&[0, 5])), vec![1]);
+        assert_eq!(mapping.partial(GUID, &inputs(&[4])), vec![1]);
+
+        let traces = mapping.trace(GUID, &inputs(&[0])).unwrap();
+        assert!(traces[0].touched());
+        assert_eq!(traces[0].missing().len(), 2);
+    }
+
+    #[test]
+    fn exact_any_group_rejects_other_inputs() {
+        let mut mapping = Mapping::new();
+        push_any(&mut mapping, &[0], &[&[4, 5]], 1);
+        mapping.set_exact([1].into_iter().collect());
+
+        assert_eq!(mapping.sprites(GUID, &inputs(&[0, 4])), vec![1]);
+        assert_eq!(mapping.sprites(GUID, &inputs(&[0, 4, 5])), vec![1]);
+        assert!(mapping.sprites(GUID, &inputs(&[0, 4, 1])).is_empty());
+    }
+
+    #[test]
+    fn text_lists_sorted_inputs() {
+        let mut mapping = Mapping::new();
+        mapping.push(GUID, &inputs(&[1, 0]), 2);
+        push_any(&mut mapping, &[3], &[&[5, 4]], 1);
+        mapping.set_limits(GUID, vec![PinnedAxis::new(0, 0, -100, 100)]);
+
+        let text = mapping.to_text(|sprite| (sprite == 2).then(|| "Up".to_string()));
+
+        assert_eq!(
+            text,
+            format!(
+                "{}:\n  Up (2): b0, b1\n  Unknown (1): b3, any (b4 | b5)\n  \
+                 limits a0: default 0, min -100, max 100\n",
+                GUID
+            )
+        );
+    }
+
+    #[test]
+    fn selected_saved_only_when_set() {
+        let mut mapping = Mapping::new();
+        let text = serde_yaml::to_string(&mapping).unwrap();
+
+        assert!(!text.contains("selected"));
+
+        mapping.set_selected(1, Some(format!("{}#2", GUID)));
+        let text = serde_yaml::to_string(&mapping).unwrap();
+        let loaded: Mapping = serde_yaml::from_str(&text).unwrap();
+
+        assert_eq!(loaded.selected(0), None);
+        assert_eq!(loaded.selected(1), Some(&format!("{}#2", GUID)));
+    }
+
+    #[test]
+    fn push_replaces_sprite_mapping() {
+        let mut mapping = Mapping::new();
+        mapping.push(GUID, &inputs(&[0]), 1);
+        mapping.push(GUID, &inputs(&[1]), 1);
+
+        assert_eq!(sprites(&mapping), vec![(1, 1)]);
+        assert!(mapping.sprites(GUID, &inputs(&[0])).is_empty());
+        assert_eq!(mapping.sprites(GUID, &inputs(&[1])), vec![1]);
+    }
+
+    #[test]
+    fn push_empty_removes_sprite_mapping() {
+        let mut mapping = Mapping::new();
+        mapping.push(GUID, &inputs(&[0]), 1);
+        mapping.push(GUID, &inputs(&[]), 1);
+
+        assert!(sprites(&mapping).is_empty());
+        assert!(mapping.sprites(GUID, &inputs(&[0])).is_empty());
+    }
+
+    #[test]
+    fn push_empty_keeps_other_sprites() {
+        let mut mapping = Mapping::new();
+        mapping.push(GUID, &inputs(&[0]), 1);
+        mapping.push(GUID, &inputs(&[1]), 2);
+        mapping.push(GUID, &inputs(&[]), 1);
+
+        assert_eq!(sprites(&mapping), vec![(2, 1)]);
+        assert_eq!(mapping.sprites(GUID, &inputs(&[0, 1])), vec![2]);
+    }
+
+    #[test]
+    fn sprites_sorted_by_button_count() {
+        let mut mapping = Mapping::new();
+        mapping.push(GUID, &inputs(&[0]), 3);
+        mapping.push(GUID, &inputs(&[0, 1, 2]), 2);
+        mapping.push(GUID, &inputs(&[1]), 0);
+        mapping.push(GUID, &inputs(&[0, 1]), 1);
+
+        assert_eq!(sprites(&mapping), vec![(2, 3), (1, 2), (0, 1), (3, 1)]);
+        assert_eq!(mapping.sprites(GUID, &inputs(&[0, 1, 2])), vec![2, 1, 0, 3]);
+    }
 }
@@ -1,5 +1,6 @@
 use std::path::Path;
 use std::path::PathBuf;
+use std::time::Duration;
 use structopt::StructOpt;
 
 #[derive(StructOpt, Debug)]
@@ -14,10 +15,149 @@ pub struct Options {
         parse(from_os_str)
     )]
     config_path: PathBuf,
+
+    #[structopt(
+        short = "w",
+        long = "windows",
+        name = "WINDOWS",
+        help = "Open WINDOWS windows, one for every controller",
+        default_value = "1"
+    )]
+    windows: usize,
+
+    #[structopt(
+        short = "d",
+        long = "device",
+        name = "DEVICE",
+        help = "Show only controller with GUID DEVICE, repeat for every window",
+        number_of_values = 1
+    )]
+    devices: Vec<String>,
+
+    #[structopt(
+        long = "controllerdb",
+        name = "CONTROLLER_DB",
+        help = "Load game controller mappings from CONTROLLER_DB file",
+        parse(from_os_str)
+    )]
+    controller_db: Option<PathBuf>,
+
+    #[structopt(long = "sound", help = "Play sprite sounds on activation")]
+    sound: bool,
+
+    #[structopt(
+        long = "poll-rate",
+        name = "POLL_RATE",
+        help = "Read controllers POLL_RATE times per second independent of frame rate"
+    )]
+    poll_rate: Option<u32>,
+
+    #[structopt(long = "hide-cursor", help = "Hide mouse cursor over window")]
+    hide_cursor: bool,
+
+    #[structopt(long = "fullscreen", help = "Show windows in fullscreen mode")]
+    fullscreen: bool,
+
+    #[structopt(
+        long = "display",
+        name = "DISPLAY",
+        help = "Show windows on display with index DISPLAY"
+    )]
+    display: Option<i32>,
+
+    #[structopt(long = "reset-help", help = "Show help message again")]
+    reset_help: bool,
+
+    #[structopt(
+        long = "wait-events",
+        help = "Draw next frame as soon as input event received instead of fixed sleep"
+    )]
+    wait_events: bool,
+
+    #[structopt(long = "watch", help = "Reload preferences file when it changed")]
+    watch: bool,
+
+    #[structopt(
+        long = "minimize-on-close",
+        help = "Minimize window to task bar when it closed (no system tray icon), close with Shift held to exit"
+    )]
+    minimize_on_close: bool,
+
+    #[structopt(
+        long = "timeline",
+        name = "TIMELINE",
+        help = "Write every input press and release to CSV file TIMELINE",
+        parse(from_os_str)
+    )]
+    timeline: Option<PathBuf>,
+
+    #[structopt(
+        long = "no-gui-errors",
+        help = "Print errors to stderr instead of showing message box"
+    )]
+    no_gui_errors: bool,
 }
 
 impl Options {
     pub fn config_path(&self) -> &Path {
         &self.config_path
     }
+
+    pub fn windows(&self) -> usize {
+        self.windows
+    }
+
+    pub fn device(&self, window: usize) -> Option<&str> {
+        self.devices.get(window).map(String::as_str)
+    }
+
+    pub fn controller_db(&self) -> Option<&Path> {
+        self.controller_db.as_deref()
+    }
+
+    pub fn sound(&self) -> bool {
+        self.sound
+    }
+
+    pub fn hide_cursor(&self) -> bool {
+        self.hide_cursor
+    }
+
+    pub fn fullscreen(&self) -> bool {
+        self.fullscreen
+    }
+
+    pub fn display(&self) -> Option<i32> {
+        self.display
+    }
+
+    pub fn reset_help(&self) -> bool {
+        self.reset_help
+    }
+
+    pub fn wait_events(&self) -> bool {
+        self.wait_events
+    }
+
+    pub fn watch(&self) -> bool {
+        self.watch
+    }
+
+    pub fn minimize_on_close(&self) -> bool {
+        self.minimize_on_close
+    }
+
+    pub fn timeline(&self) -> Option<&Path> {
+        self.timeline.as_deref()
+    }
+
+    pub fn no_gui_errors(&self) -> bool {
+        self.no_gui_errors
+    }
+
+    pub fn poll_time(&self) -> Option<Duration> {
+        self.poll_rate
+            .filter(|&rate| rate > 0)
+            .map(|rate| Duration::from_secs(1) / rate)
+    }
 }
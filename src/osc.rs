@@ -0,0 +1,105 @@
+#[cfg(feature = "osc")]
+use std::net::UdpSocket;
+
+use crate::config::OscTarget;
+use crate::error::ApplicationResult;
+
+#[cfg(feature = "osc")]
+pub struct Osc {
+    socket: Option<UdpSocket>,
+    prefix: String,
+}
+
+#[cfg(feature = "osc")]
+impl Osc {
+    pub fn empty() -> Self {
+        Self {
+            socket: None,
+            prefix: String::new(),
+        }
+    }
+
+    pub fn open(target: &OscTarget) -> ApplicationResult<Self> {
+        let socket = UdpSocket::bind("0.0.0.0:0")?;
+        socket.connect(target.address())?;
+
+        Ok(Self {
+            socket: Some(socket),
+            prefix: target.prefix().into(),
+        })
+    }
+
+    /// Sends input state, address is prefix, kind and sanitized name.
+    pub fn send(&self, kind: &str, name: &str, pressed: bool) {
+        if let Some(ref socket) = self.socket {
+            let address = format!("{}/{}/{}", self.prefix, kind, sanitize(name));
+            let _ = socket.send(&message(&address, pressed as i32));
+        }
+    }
+}
+
+#[cfg(not(feature = "osc"))]
+pub struct Osc;
+
+#[cfg(not(feature = "osc"))]
+impl Osc {
+    pub fn empty() -> Self {
+        Self
+    }
+
+    pub fn open(_target: &OscTarget) -> ApplicationResult<Self> {
+        Err("Application built without OSC support".to_string().into())
+    }
+
+    pub fn send(&self, _kind: &str, _name: &str, _pressed: bool) {}
+}
+
+/// Replaces characters not allowed in OSC address with underscore.
+#[cfg_attr(not(feature = "osc"), allow(dead_code))]
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|ch| match ch {
+            ch if ch.is_ascii_alphanumeric() || ch == '-' || ch == '_' => ch,
+            _ => '_',
+        })
+        .collect()
+}
+
+/// Encodes OSC message with single integer argument.
+#[cfg_attr(not(feature = "osc"), allow(dead_code))]
+fn message(address: &str, value: i32) -> Vec<u8> {
+    let mut message = Vec::new();
+    push_string(&mut message, address);
+    push_string(&mut message, ",i");
+    message.extend_from_slice(&value.to_be_bytes());
+
+    message
+}
+
+/// Appends null terminated string padded to multiple of four bytes.
+#[cfg_attr(not(feature = "osc"), allow(dead_code))]
+fn push_string(message: &mut Vec<u8>, text: &str) {
+    message.extend_from_slice(text.as_bytes());
+    message.push(0);
+
+    while !message.len().is_multiple_of(4) {
+        message.push(0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn message_padded_to_four_bytes() {
+        assert_eq!(message("/a/b", 1), b"/a/b\0\0\0\0,i\0\0\0\0\0\x01".to_vec());
+        assert_eq!(message("/abc", -1).len(), 16);
+    }
+
+    #[test]
+    fn sanitize_replaces_reserved_characters() {
+        assert_eq!(sanitize("a2 max"), "a2_max");
+        assert_eq!(sanitize("Left Stick/Up"), "Left_Stick_Up");
+    }
+}
@@ -0,0 +1,129 @@
+use std::cell::Cell;
+use std::cell::Ref;
+use std::cell::RefCell;
+use std::cell::RefMut;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
+
+use crate::config::Config;
+use crate::error::ApplicationResult;
+use crate::mapping::Mapping;
+
+/// Mapping stored in preferences file, shared by all windows. Mapping is
+/// loaded and saved as a whole, so windows never overwrite changes of each
+/// other.
+pub struct Preferences {
+    path: PathBuf,
+    mapping: RefCell<Mapping>,
+    modified: Cell<bool>,
+    last_save: Cell<Instant>,
+    file_time: Cell<Option<SystemTime>>,
+    revision: Cell<u64>,
+}
+
+impl Preferences {
+    /// Loads mapping from preferences file, empty mapping used if file does
+    /// not exist.
+    pub fn load(path: PathBuf, config: &Config) -> ApplicationResult<Self> {
+        let mut mapping = match path.exists() {
+            true => Mapping::load(&path)?,
+            false => Mapping::new(),
+        };
+        mapping.set_exact(
+            config
+                .sprites()
+                .iter()
+                .enumerate()
+                .filter(|(_, sprite)| sprite.exact())
+                .map(|(id, _)| id)
+                .collect(),
+        );
+        mapping.set_pressed_limits(
+            config
+                .sprites()
+                .iter()
+                .enumerate()
+                .filter_map(|(id, sprite)| sprite.pressed_limits().map(|limits| (id, limits)))
+                .collect(),
+        );
+
+        Ok(Self {
+            file_time: Cell::new(modified_time(&path)),
+            path,
+            mapping: RefCell::new(mapping),
+            modified: Cell::new(false),
+            last_save: Cell::new(Instant::now()),
+            revision: Cell::new(0),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn mapping(&self) -> Ref<'_, Mapping> {
+        self.mapping.borrow()
+    }
+
+    /// Returns mapping for change, mapping marked modified.
+    pub fn mapping_mut(&self) -> RefMut<'_, Mapping> {
+        self.modified.set(true);
+        self.mapping.borrow_mut()
+    }
+
+    /// Checks that mapping has changes not saved to file.
+    pub fn modified(&self) -> bool {
+        self.modified.get()
+    }
+
+    /// Checks that mapping modified and last save was at least interval ago.
+    pub fn save_due(&self, interval: Duration) -> bool {
+        self.modified.get() && self.last_save.get().elapsed() >= interval
+    }
+
+    pub fn save(&self) -> ApplicationResult<()> {
+        self.mapping.borrow().save(&self.path)?;
+        self.modified.set(false);
+        self.last_save.set(Instant::now());
+        self.file_time.set(modified_time(&self.path));
+
+        Ok(())
+    }
+
+    /// Checks that preferences file changed outside of application since
+    /// last load or save.
+    pub fn changed(&self) -> bool {
+        let file_time = modified_time(&self.path);
+
+        file_time.is_some() && file_time != self.file_time.get()
+    }
+
+    /// Replaces mapping with mapping loaded from file. Mapping kept if file
+    /// can not be loaded, file is not loaded again until it changes.
+    pub fn reload(&self) -> ApplicationResult<()> {
+        self.file_time.set(modified_time(&self.path));
+        let loaded = Mapping::load(&self.path)?;
+        self.mapping.borrow_mut().replace(loaded);
+        self.modified.set(false);
+        self.revision.set(self.revision.get() + 1);
+
+        Ok(())
+    }
+
+    /// Returns number of reloads, every window applies reloaded mapping when
+    /// number changes.
+    pub fn revision(&self) -> u64 {
+        self.revision.get()
+    }
+}
+
+/// Returns modification time of file, `None` if file can not be read.
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
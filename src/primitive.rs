@@ -0,0 +1,48 @@
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::surface::Surface;
+
+use crate::config::Primitive;
+use crate::config::Shape;
+use crate::error::ApplicationResult;
+
+/// Renders primitive to surface with size of primitive rectangle. Edges of
+/// circles and rings are anti-aliased using pixel coverage.
+pub fn render(primitive: &Primitive) -> ApplicationResult<Surface<'static>> {
+    let width = primitive.rect().width();
+    let height = primitive.rect().height();
+    let [red, green, blue, alpha] = primitive.color();
+    let mut surface = Surface::new(width, height, PixelFormatEnum::RGBA32)?;
+    let pitch = surface.pitch() as usize;
+    let radius = width.min(height) as f32 / 2.0;
+    let thickness = primitive.thickness() as f32;
+
+    surface.with_lock_mut(|pixels| {
+        for y in 0..height as usize {
+            for x in 0..width as usize {
+                let dx = x as f32 + 0.5 - width as f32 / 2.0;
+                let dy = y as f32 + 0.5 - height as f32 / 2.0;
+                let distance = (dx * dx + dy * dy).sqrt();
+                let coverage = match primitive.shape() {
+                    Shape::Rectangle => 1.0,
+                    Shape::Circle => coverage(radius, distance),
+                    Shape::Ring => {
+                        coverage(radius, distance) - coverage(radius - thickness, distance)
+                    }
+                };
+                let offset = y * pitch + 4 * x;
+
+                pixels[offset] = red;
+                pixels[offset + 1] = green;
+                pixels[offset + 2] = blue;
+                pixels[offset + 3] = (alpha as f32 * coverage).round() as u8;
+            }
+        }
+    });
+
+    Ok(surface)
+}
+
+/// Returns part of pixel covered by circle with given radius.
+fn coverage(radius: f32, distance: f32) -> f32 {
+    (radius - distance + 0.5).clamp(0.0, 1.0)
+}
@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+
+use crate::config::InputRemap;
+use crate::mapping::Input;
+
+/// Table of input replacements applied to controller inputs before they are
+/// pressed. Inputs without remap are kept as is.
+#[derive(Debug, Default)]
+pub struct Remap {
+    all: HashMap<Input, Input>,
+    guids: HashMap<(String, Input), Input>,
+}
+
+impl Remap {
+    pub fn new(remaps: &[InputRemap]) -> Self {
+        let mut remap = Self::default();
+
+        for entry in remaps {
+            match entry.guid() {
+                Some(guid) => {
+                    let key = (guid.to_string(), entry.from().clone());
+
+                    remap.guids.insert(key, entry.to().clone());
+                }
+                None => {
+                    remap.all.insert(entry.from().clone(), entry.to().clone());
+                }
+            }
+        }
+
+        remap
+    }
+
+    /// Returns remapped input: controller specific remap, then remap for all
+    /// controllers, then input itself.
+    pub fn apply(&self, guid: &str, input: Input) -> Input {
+        let key = (guid.to_string(), input);
+
+        match self.guids.get(&key).or_else(|| self.all.get(&key.1)) {
+            Some(input) => input.clone(),
+            None => key.1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::mapping::State;
+
+    const GUID: &str = "030000005e0400008e02000010010000";
+
+    fn remap(yaml: &str) -> Remap {
+        let remaps: Vec<InputRemap> = serde_yaml::from_str(yaml).unwrap();
+
+        Remap::new(&remaps)
+    }
+
+    #[test]
+    fn identity_without_remap() {
+        let remap = remap("[]");
+
+        assert_eq!(remap.apply(GUID, Input::button(1)), Input::button(1));
+        assert_eq!(remap.apply(GUID, Input::axis_min(0)), Input::axis_min(0));
+    }
+
+    #[test]
+    fn remaps_buttons_axes_and_hats() {
+        let remap = remap(
+            "
+- { from: !Button { button: 11 }, to: !Hat { hat: 0, state: Up } }
+- { from: !Axis { axis: 1, direction: Minimum }, to: !Axis { axis: 1, direction: Maximum } }
+- { from: !Hat { hat: 1, state: Left }, to: !Button { button: 4 } }
+",
+        );
+
+        assert_eq!(
+            remap.apply(GUID, Input::button(11)),
+            Input::hat(0, State::Up)
+        );
+        assert_eq!(remap.apply(GUID, Input::axis_min(1)), Input::axis_max(1));
+        assert_eq!(
+            remap.apply(GUID, Input::hat(1, State::Left)),
+            Input::button(4)
+        );
+        assert_eq!(remap.apply(GUID, Input::axis_max(1)), Input::axis_max(1));
+    }
+
+    #[test]
+    fn guid_remap_takes_precedence() {
+        let remap = remap(&format!(
+            "
+- {{ from: !Button {{ button: 0 }}, to: !Button {{ button: 1 }} }}
+- {{ guid: \"{}\", from: !Button {{ button: 0 }}, to: !Button {{ button: 2 }} }}
+",
+            GUID
+        ));
+
+        assert_eq!(remap.apply(GUID, Input::button(0)), Input::button(2));
+        assert_eq!(remap.apply("other", Input::button(0)), Input::button(1));
+    }
+
+    #[test]
+    fn swap_round_trip() {
+        let remap = remap(
+            "
+- { from: !Axis { axis: 0, direction: Minimum }, to: !Axis { axis: 0, direction: Maximum } }
+- { from: !Axis { axis: 0, direction: Maximum }, to: !Axis { axis: 0, direction: Minimum } }
+- { from: !Button { button: 0 }, to: !Button { button: 1 } }
+- { from: !Button { button: 1 }, to: !Button { button: 0 } }
+",
+        );
+
+        for input in [
+            Input::axis_min(0),
+            Input::axis_max(0),
+            Input::button(0),
+            Input::button(1),
+            Input::button(2),
+        ] {
+            let remapped = remap.apply(GUID, input.clone());
+
+            assert_eq!(remap.apply(GUID, remapped), input);
+        }
+    }
+}
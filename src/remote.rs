@@ -0,0 +1,72 @@
+use std::path::Path;
+use std::path::PathBuf;
+
+#[cfg(feature = "http")]
+use std::fs;
+#[cfg(feature = "http")]
+use std::fs::File;
+#[cfg(feature = "http")]
+use std::io;
+
+use crate::error::ApplicationResult;
+
+/// Returns local path for resource. Resources with `http` or `https` URL are
+/// downloaded once to cache directory and loaded from cache later.
+pub fn resolve(path: &Path, cache: &Path) -> ApplicationResult<PathBuf> {
+    match path.to_str() {
+        Some(url) if url.starts_with("http://") || url.starts_with("https://") => {
+            let cached = cache_path(url, cache);
+
+            if cached.exists() {
+                Ok(cached)
+            } else {
+                fetch(url, cache, &cached)?;
+
+                Ok(cached)
+            }
+        }
+        _ => Ok(path.into()),
+    }
+}
+
+/// Returns cache file name based on FNV-1a hash of URL, which is stable
+/// between runs.
+fn cache_path(url: &str, cache: &Path) -> PathBuf {
+    let hash = url.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    });
+    let name = url.split(['?', '#']).next().unwrap_or(url);
+    let extension = name
+        .rsplit('/')
+        .next()
+        .and_then(|name| name.rsplit_once('.'));
+
+    match extension {
+        Some((_, extension)) => cache.join(format!("{:016x}.{}", hash, extension)),
+        None => cache.join(format!("{:016x}", hash)),
+    }
+}
+
+#[cfg(feature = "http")]
+fn fetch(url: &str, cache: &Path, path: &Path) -> ApplicationResult<()> {
+    fs::create_dir_all(cache)?;
+
+    let response = ureq::get(url)
+        .call()
+        .map_err(|error| format!("Can not download {} (not cached): {}", url, error))?;
+    let partial = path.with_extension("part");
+    let mut file = File::create(&partial)?;
+    io::copy(&mut response.into_reader(), &mut file)?;
+    fs::rename(&partial, path)?;
+
+    Ok(())
+}
+
+#[cfg(not(feature = "http"))]
+fn fetch(url: &str, _cache: &Path, _path: &Path) -> ApplicationResult<()> {
+    Err(format!(
+        "Application built without HTTP support, can not load {}",
+        url
+    )
+    .into())
+}
@@ -0,0 +1,70 @@
+#[cfg(feature = "sound")]
+use std::collections::HashMap;
+
+#[cfg(feature = "sound")]
+use sdl2::mixer;
+#[cfg(feature = "sound")]
+use sdl2::mixer::Channel;
+#[cfg(feature = "sound")]
+use sdl2::mixer::Chunk;
+
+use crate::config::Config;
+use crate::error::ApplicationResult;
+
+#[cfg(feature = "sound")]
+const CHUNK_SIZE: i32 = 1_024;
+
+#[cfg(feature = "sound")]
+pub struct Sounds {
+    chunks: HashMap<usize, Chunk>,
+}
+
+#[cfg(feature = "sound")]
+impl Sounds {
+    pub fn empty() -> Self {
+        Self {
+            chunks: HashMap::new(),
+        }
+    }
+
+    pub fn open(config: &Config) -> ApplicationResult<Self> {
+        mixer::open_audio(
+            mixer::DEFAULT_FREQUENCY,
+            mixer::DEFAULT_FORMAT,
+            mixer::DEFAULT_CHANNELS,
+            CHUNK_SIZE,
+        )?;
+
+        let mut chunks = HashMap::new();
+
+        for (id, sprite) in config.sprites().iter().enumerate() {
+            if let Some(path) = sprite.sound() {
+                chunks.insert(id, Chunk::from_file(path)?);
+            }
+        }
+
+        Ok(Self { chunks })
+    }
+
+    pub fn play(&self, sprite: usize) {
+        if let Some(chunk) = self.chunks.get(&sprite) {
+            let _ = Channel::all().play(chunk, 0);
+        }
+    }
+}
+
+#[cfg(not(feature = "sound"))]
+pub struct Sounds;
+
+#[cfg(not(feature = "sound"))]
+impl Sounds {
+    pub fn empty() -> Self {
+        Self
+    }
+
+    pub fn open(_config: &Config) -> ApplicationResult<Self> {
+        Err("Application built without sound support".to_string().into())
+    }
+
+    pub fn play(&self, _sprite: usize) {}
+}
@@ -0,0 +1,251 @@
+use serde::Deserialize;
+
+/// User visible overlay messages. Messages can be replaced in configuration,
+/// placeholders in braces are replaced with actual values.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct Strings {
+    help: String,
+    binding: String,
+    active_keys: String,
+    no_active_keys: String,
+    save_keys: String,
+    skip_keys: String,
+    reset_keys: String,
+    setup_paused: String,
+    setup_paused_keys: String,
+    controllers: String,
+    mapping_saved: String,
+    preferences_reloaded: String,
+    preferences_error: String,
+    limits_pinned: String,
+    limits_error: String,
+    limits_load_error: String,
+    setup_command_error: String,
+    controller_db_loaded: String,
+    controller_db_error: String,
+    sounds_error: String,
+    osc_error: String,
+    controller_selected: String,
+    selection_cleared: String,
+    calibrating: String,
+    calibrate_extremes: String,
+    calibrate_center: String,
+    axes_moved: String,
+    calibration_keys: String,
+    calibration_saved: String,
+    calibration_empty: String,
+    calibration_disconnected: String,
+    no_controller: String,
+    too_many_inputs: String,
+}
+
+impl Default for Strings {
+    fn default() -> Self {
+        Self {
+            help: "Use F1 to start mapping.\nPress any button to hide message.".into(),
+            binding: "Binding input for {name}.".into(),
+            active_keys: "Active keys: {keys}".into(),
+            no_active_keys: "No active keys".into(),
+            save_keys: "Press: F1 - save, F2 - cancel mapping,".into(),
+            skip_keys: "Press: F1 - skip, F2 - cancel mapping,".into(),
+            reset_keys: "       F3 - reset limits.".into(),
+            setup_paused: "Controller disconnected - reconnect to continue.".into(),
+            setup_paused_keys: "Press: F2 - cancel mapping,".into(),
+            controllers: "Controllers:".into(),
+            mapping_saved: "Mapping saved.".into(),
+            preferences_reloaded: "Preferences reloaded.".into(),
+            preferences_error: "Can not reload preferences: {error}".into(),
+            limits_pinned: "Axis limits pinned.".into(),
+            limits_error: "Can not save axis limits: {error}".into(),
+            limits_load_error: "Can not load axis limits: {error}".into(),
+            setup_command_error: "Can not run setup command: {error}".into(),
+            controller_db_loaded: "Loaded {count} controller mappings.".into(),
+            controller_db_error: "Can not load controller mappings: {error}".into(),
+            sounds_error: "Can not open sounds: {error}".into(),
+            osc_error: "Can not open OSC output: {error}".into(),
+            controller_selected: "Controller: {name}".into(),
+            selection_cleared: "Controller selection cleared.".into(),
+            calibrating: "Calibrating {name}.".into(),
+            calibrate_extremes: "Move every axis to its extremes,\nthen press F8 to continue."
+                .into(),
+            calibrate_center: "Release all axes to center,\nthen press F8 to save.".into(),
+            axes_moved: "Axes moved: {moved} of {count}".into(),
+            calibration_keys: "Press: F2 - cancel calibration.".into(),
+            calibration_saved: "Calibration saved.".into(),
+            calibration_empty: "No axis moved, calibration cancelled.".into(),
+            calibration_disconnected: "Controller disconnected, calibration cancelled.".into(),
+            no_controller: "No controller to calibrate.".into(),
+            too_many_inputs: "Too many inputs pressed ({count}), controller may be faulty.".into(),
+        }
+    }
+}
+
+impl Strings {
+    pub fn help(&self) -> &str {
+        &self.help
+    }
+
+    pub fn binding(&self, name: &str) -> String {
+        fill(&self.binding, &[("name", name)])
+    }
+
+    pub fn active_keys(&self, keys: &str) -> String {
+        fill(&self.active_keys, &[("keys", keys)])
+    }
+
+    pub fn no_active_keys(&self) -> &str {
+        &self.no_active_keys
+    }
+
+    pub fn save_keys(&self) -> &str {
+        &self.save_keys
+    }
+
+    pub fn skip_keys(&self) -> &str {
+        &self.skip_keys
+    }
+
+    pub fn reset_keys(&self) -> &str {
+        &self.reset_keys
+    }
+
+    pub fn setup_paused(&self) -> &str {
+        &self.setup_paused
+    }
+
+    pub fn setup_paused_keys(&self) -> &str {
+        &self.setup_paused_keys
+    }
+
+    pub fn controllers(&self) -> &str {
+        &self.controllers
+    }
+
+    pub fn mapping_saved(&self) -> &str {
+        &self.mapping_saved
+    }
+
+    pub fn preferences_reloaded(&self) -> &str {
+        &self.preferences_reloaded
+    }
+
+    pub fn preferences_error(&self, error: &str) -> String {
+        fill(&self.preferences_error, &[("error", error)])
+    }
+
+    pub fn limits_pinned(&self) -> &str {
+        &self.limits_pinned
+    }
+
+    pub fn limits_error(&self, error: &str) -> String {
+        fill(&self.limits_error, &[("error", error)])
+    }
+
+    pub fn limits_load_error(&self, error: &str) -> String {
+        fill(&self.limits_load_error, &[("error", error)])
+    }
+
+    pub fn setup_command_error(&self, error: &str) -> String {
+        fill(&self.setup_command_error, &[("error", error)])
+    }
+
+    pub fn controller_db_loaded(&self, count: i32) -> String {
+        fill(&self.controller_db_loaded, &[("count", &count.to_string())])
+    }
+
+    pub fn controller_db_error(&self, error: &str) -> String {
+        fill(&self.controller_db_error, &[("error", error)])
+    }
+
+    pub fn sounds_error(&self, error: &str) -> String {
+        fill(&self.sounds_error, &[("error", error)])
+    }
+
+    pub fn osc_error(&self, error: &str) -> String {
+        fill(&self.osc_error, &[("error", error)])
+    }
+
+    pub fn controller_selected(&self, name: &str) -> String {
+        fill(&self.controller_selected, &[("name", name)])
+    }
+
+    pub fn selection_cleared(&self) -> &str {
+        &self.selection_cleared
+    }
+
+    pub fn calibrating(&self, name: &str) -> String {
+        fill(&self.calibrating, &[("name", name)])
+    }
+
+    pub fn calibrate_extremes(&self) -> &str {
+        &self.calibrate_extremes
+    }
+
+    pub fn calibrate_center(&self) -> &str {
+        &self.calibrate_center
+    }
+
+    pub fn axes_moved(&self, moved: usize, count: usize) -> String {
+        fill(
+            &self.axes_moved,
+            &[("moved", &moved.to_string()), ("count", &count.to_string())],
+        )
+    }
+
+    pub fn calibration_keys(&self) -> &str {
+        &self.calibration_keys
+    }
+
+    pub fn calibration_saved(&self) -> &str {
+        &self.calibration_saved
+    }
+
+    pub fn calibration_empty(&self) -> &str {
+        &self.calibration_empty
+    }
+
+    pub fn calibration_disconnected(&self) -> &str {
+        &self.calibration_disconnected
+    }
+
+    pub fn no_controller(&self) -> &str {
+        &self.no_controller
+    }
+
+    pub fn too_many_inputs(&self, count: usize) -> String {
+        fill(&self.too_many_inputs, &[("count", &count.to_string())])
+    }
+}
+
+/// Replaces `{name}` placeholders in template with values.
+fn fill(template: &str, values: &[(&str, &str)]) -> String {
+    values
+        .iter()
+        .fold(template.to_string(), |text, (name, value)| {
+            text.replace(&format!("{{{}}}", name), value)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fill_replaces_placeholders() {
+        assert_eq!(
+            fill("{moved} of {count}", &[("moved", "1"), ("count", "2")]),
+            "1 of 2"
+        );
+        assert_eq!(fill("{name}, {name}", &[("name", "A")]), "A, A");
+        assert_eq!(fill("{other}", &[("name", "A")]), "{other}");
+    }
+
+    #[test]
+    fn missing_strings_use_defaults() {
+        let strings: Strings = serde_yaml::from_str("binding: \"Bind {name}\"").unwrap();
+
+        assert_eq!(strings.binding("A"), "Bind A");
+        assert_eq!(strings.no_active_keys(), "No active keys");
+    }
+}
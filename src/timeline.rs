@@ -0,0 +1,89 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::io::Write;
+use std::path::Path;
+use std::time::Duration;
+use std::time::Instant;
+
+use crate::error::ApplicationResult;
+
+const FLUSH_TIME: Duration = Duration::from_secs(1);
+
+/// CSV log of input presses and releases.
+pub struct Timeline {
+    writer: BufWriter<File>,
+    start: Instant,
+    flushed: Instant,
+}
+
+impl Timeline {
+    pub fn create(path: &Path) -> ApplicationResult<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "time_ms,guid,input,event")?;
+
+        Ok(Self {
+            writer,
+            start: Instant::now(),
+            flushed: Instant::now(),
+        })
+    }
+
+    /// Writes input edge detected at given time.
+    pub fn record(
+        &mut self,
+        time: Instant,
+        guid: &str,
+        input: &str,
+        pressed: bool,
+    ) -> ApplicationResult<()> {
+        let event = match pressed {
+            true => "press",
+            false => "release",
+        };
+
+        writeln!(
+            self.writer,
+            "{},{},{},{}",
+            time.saturating_duration_since(self.start).as_millis(),
+            quote(guid),
+            quote(input),
+            event
+        )?;
+
+        Ok(())
+    }
+
+    /// Writes buffered lines to file at most once per second.
+    pub fn flush(&mut self) -> ApplicationResult<()> {
+        if self.flushed.elapsed() >= FLUSH_TIME {
+            self.close()?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes all buffered lines to file.
+    pub fn close(&mut self) -> ApplicationResult<()> {
+        self.writer.flush()?;
+        self.flushed = Instant::now();
+
+        Ok(())
+    }
+}
+
+/// Quotes CSV field, quotes inside field are doubled.
+fn quote(field: &str) -> String {
+    format!("\"{}\"", field.replace('"', "\"\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quote_escapes_quotes() {
+        assert_eq!(quote("h0 ^>"), "\"h0 ^>\"");
+        assert_eq!(quote(","), "\",\"");
+        assert_eq!(quote("\""), "\"\"\"\"");
+    }
+}
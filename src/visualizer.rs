@@ -1,95 +1,554 @@
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 use std::collections::HashSet;
-use std::path::PathBuf;
+use std::collections::VecDeque;
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+use std::time::Instant;
 
 use sdl2::image::LoadTexture;
 use sdl2::pixels::Color;
+use sdl2::rect::Rect;
 use sdl2::render::BlendMode;
 use sdl2::render::Texture;
 use sdl2::render::TextureCreator;
 use sdl2::render::WindowCanvas;
 use sdl2::JoystickSubsystem;
 
+use crate::blend;
 use crate::config::Config;
+use crate::config::Counter;
+use crate::config::DefaultSelection;
+use crate::config::Filter;
+use crate::config::Grow;
+use crate::config::Rect as RectConfig;
+use crate::config::Rumble;
+use crate::config::Sprite as SpriteConfig;
+use crate::config::TextAnchor;
 use crate::error::ApplicationResult;
 use crate::font::Font;
+use crate::joysticks::device_guid;
 use crate::joysticks::Joysticks;
-use crate::mapping::Mapping;
+use crate::mapping::Input;
+use crate::mapping::PinnedAxis;
+use crate::osc::Osc;
+use crate::preferences::Preferences;
+use crate::primitive;
+use crate::sound::Sounds;
+use crate::strings::Strings;
+use crate::timeline::Timeline;
+
+const PARTIAL_ALPHA: f32 = 0.5;
+const TEXT_MARGIN: i32 = 8;
+const TOAST_TIME: Duration = Duration::from_secs(2);
+const FPS_WINDOW: Duration = Duration::from_secs(1);
+const WATCH_TIME: Duration = Duration::from_secs(1);
+const CALIBRATION_TRAVEL: i32 = 6_554;
 
 pub struct Visualiser<'a> {
-    background: Texture<'a>,
+    background: Option<Texture<'a>>,
     sprites: HashMap<usize, Sprite<'a>>,
-    default: HashSet<usize>,
-    preferences: PathBuf,
+    default: Vec<usize>,
+    bars: Vec<usize>,
+    players: Vec<(usize, u8)>,
+    matched: HashMap<usize, Instant>,
+    presses: HashMap<usize, u32>,
+    active_frames: HashMap<usize, u64>,
+    shown: Vec<usize>,
+    appeared: HashMap<usize, Instant>,
+    partial: Vec<usize>,
+    color: u8,
+    alpha: u8,
+    premultiplied: bool,
+    grouping: InputGrouping,
+    preferences: &'a Preferences,
+    window: usize,
     font: &'a Font<'a>,
+    text_anchor: TextAnchor,
+    strings: Strings,
+    sounds: &'a Sounds,
+    osc: &'a Osc,
+    edges: InputEdges,
+    timeline: Option<Timeline>,
     show_help: bool,
-    mapping: Mapping,
+    show_diagnostics: bool,
+    show_fps: bool,
+    show_heatmap: bool,
+    frames: FrameCounter,
+    toast: Option<(String, Instant)>,
+    warnings: Vec<String>,
+    hide_input: Option<Input>,
+    hide_held: bool,
+    setup_controller: Option<String>,
+    layers: Vec<(String, Input)>,
+    group_layers: HashMap<usize, HashSet<String>>,
+    setup_command: Option<Vec<String>>,
+    save_interval: Duration,
+    save_text: bool,
+    watch: Option<Instant>,
+    revision: u64,
     joysticks: Joysticks,
     setup: SetupOverlay,
+    calibration: Option<Calibration>,
+    icons: Option<IconSheet<'a>>,
+    target: Option<Texture<'a>>,
 }
 
 impl<'a> Visualiser<'a> {
+    #[allow(clippy::too_many_arguments)]
     pub fn create<'b, T>(
         config: &Config,
-        preferences: PathBuf,
+        preferences: &'b Preferences,
+        window: usize,
+        device: Option<&str>,
         font: &'b Font,
+        sounds: &'b Sounds,
+        osc: &'b Osc,
         texture_creator: &'b TextureCreator<T>,
         joystick_subsystem: &JoystickSubsystem,
     ) -> ApplicationResult<Visualiser<'b>> {
-        let background = texture_creator.load_texture(config.background())?;
+        let color = (255.0 * config.brightness()) as u8;
+        let alpha = (255.0 * config.global_alpha()) as u8;
+        let premultiplied = config.premultiplied_alpha();
+        let background = match config.background() {
+            Some(path) => {
+                let mut background = blend::load(path, premultiplied, texture_creator)?;
+                blend::set_modulation(&mut background, color, alpha, premultiplied);
+
+                if alpha < 255 && !premultiplied {
+                    background.set_blend_mode(BlendMode::Blend);
+                }
+
+                Some(background)
+            }
+            None => None,
+        };
+
+        let target = match config.offscreen() {
+            true => {
+                let (width, height) = match (config.size(), background.as_ref()) {
+                    (Some(size), _) => size,
+                    (None, Some(background)) => {
+                        let query = background.query();
+
+                        (query.width, query.height)
+                    }
+                    (None, None) => return Err("Window size not defined".to_string().into()),
+                };
+                let target = texture_creator.create_texture_target(None, width, height)?;
+                blend::set_filter(&target, Filter::Linear);
+
+                Some(target)
+            }
+            false => None,
+        };
+
         let mut sprites = HashMap::new();
-        let mut default = HashSet::new();
+        let mut default = Vec::new();
+        let mut bars = Vec::new();
+        let mut players = Vec::new();
         let mut bindable = Vec::new();
 
         for (id, sprite) in config.sprites().iter().enumerate() {
-            let group = sprite.group();
-            let name = sprite.name();
-            let texture = texture_creator.load_texture(sprite.path())?;
+            let (mut texture, rect) = match (sprite.primitive(), sprite.path()) {
+                (Some(primitive), _) => {
+                    let surface = primitive::render(primitive)?;
+                    let mut texture = blend::texture(surface, premultiplied, texture_creator)?;
+
+                    if !premultiplied {
+                        texture.set_blend_mode(BlendMode::Blend);
+                    }
+
+                    (texture, Some(to_rect(primitive.rect())))
+                }
+                (None, Some(path)) => (blend::load(path, premultiplied, texture_creator)?, None),
+                (None, None) => {
+                    return Err(format!("Sprite {} has no image", sprite.name()).into());
+                }
+            };
+            blend::set_modulation(&mut texture, color, alpha, premultiplied);
+
+            let rect = match sprite.rect() {
+                Some(placement) => {
+                    let query = texture.query();
+                    let base = rect.unwrap_or_else(|| Rect::new(0, 0, query.width, query.height));
+
+                    Some(Rect::new(
+                        placement.x().unwrap_or_else(|| base.x()),
+                        placement.y().unwrap_or_else(|| base.y()),
+                        placement.width().unwrap_or_else(|| base.width()),
+                        placement.height().unwrap_or_else(|| base.height()),
+                    ))
+                }
+                None => rect,
+            };
+
+            if let Some(filter) = sprite.filter() {
+                blend::set_filter(&texture, filter);
+            }
+
+            let bar = sprite.bar().map(|bar| Bar {
+                axis: bar.axis(),
+                grow: bar.grow(),
+                rect: to_rect(bar.rect()),
+            });
 
-            sprites.insert(id, Sprite::new(group, name, texture));
+            sprites.insert(id, Sprite::new(sprite, texture, bar, rect));
 
-            if sprite.default() {
-                default.insert(id);
+            if sprite.bar().is_some() {
+                bars.push(id);
+            } else if let Some(player) = sprite.player() {
+                players.push((id, player));
+            } else if sprite.default() {
+                default.push(id);
             } else {
                 bindable.push(id)
             }
         }
 
-        let mapping = match preferences.exists() {
-            true => Mapping::load(&preferences)?,
-            false => Mapping::new(),
+        order_defaults(&mut default, config.default_selection(), |id| {
+            config.sprites()[id].z()
+        });
+
+        let icons = match config.icons() {
+            Some(icons) => {
+                let texture = texture_creator.load_texture(icons.path())?;
+                let columns = (texture.query().width / icons.size()).max(1);
+
+                Some(IconSheet {
+                    texture,
+                    size: icons.size(),
+                    columns,
+                    cells: icons
+                        .inputs()
+                        .iter()
+                        .map(|icon| (icon.input().clone(), icon.cell()))
+                        .collect(),
+                })
+            }
+            None => None,
         };
 
+        let mut group_layers: HashMap<usize, HashSet<String>> = HashMap::new();
+
+        for sprite in config.sprites() {
+            if let Some(layer) = sprite.layer() {
+                group_layers
+                    .entry(sprite.group())
+                    .or_default()
+                    .insert(layer.into());
+            }
+        }
+
+        let limits_path = preferences.path().with_file_name("limits.yaml");
+        let mut joysticks = Joysticks::create(config, joystick_subsystem, device, &limits_path)?;
+        let mut warnings = Vec::new();
+
+        if let Err(error) = joysticks.load_limits() {
+            let message = config.strings().limits_load_error(&error.to_string());
+            warnings.push(message);
+        }
+        let selected = preferences.mapping().selected(window).cloned();
+
+        if let Some(device) =
+            device.filter(|&device| selected.is_none_or(|selected| selected != device))
+        {
+            preferences
+                .mapping_mut()
+                .set_selected(window, Some(device.into()));
+        }
+
+        joysticks.set_selected(preferences.mapping().selected(window).cloned());
+
+        for (guid, limits) in preferences.mapping().limits() {
+            for pinned in limits {
+                joysticks.pin_limits(guid, pinned);
+            }
+        }
+
         Ok(Visualiser {
             background,
             sprites,
             default,
+            bars,
+            players,
+            matched: HashMap::new(),
+            presses: HashMap::new(),
+            active_frames: HashMap::new(),
+            shown: Vec::new(),
+            appeared: HashMap::new(),
+            partial: Vec::new(),
+            color,
+            alpha,
+            premultiplied,
+            grouping: InputGrouping::new(config.grouping()),
             preferences,
+            window,
             font,
-            show_help: true,
-            mapping,
-            joysticks: Joysticks::create(joystick_subsystem)?,
+            text_anchor: config.text_anchor(),
+            strings: config.strings().clone(),
+            sounds,
+            osc,
+            edges: InputEdges::new(),
+            timeline: None,
+            show_help: !preferences.mapping().help_dismissed(),
+            show_diagnostics: false,
+            show_fps: false,
+            show_heatmap: false,
+            frames: FrameCounter::new(),
+            toast: None,
+            warnings,
+            hide_input: config.hide_input().cloned(),
+            hide_held: false,
+            setup_controller: None,
+            layers: config
+                .layers()
+                .iter()
+                .map(|layer| (layer.name().into(), layer.modifier().clone()))
+                .collect(),
+            group_layers,
+            setup_command: config.setup_command().map(Into::into),
+            save_interval: config.save_interval(),
+            save_text: config.preferences_text(),
+            watch: None,
+            revision: preferences.revision(),
+            joysticks,
             setup: SetupOverlay::new(&bindable),
+            calibration: None,
+            icons,
+            target,
         })
     }
 
     pub fn joystick_add(
         &mut self,
         joystick_subsystem: &JoystickSubsystem,
-        id: u32,
+        index: u32,
     ) -> ApplicationResult<()> {
-        self.joysticks.add(joystick_subsystem, id)
+        self.joysticks.add(joystick_subsystem, index)
     }
 
     pub fn joystick_remove(&mut self, id: u32) {
         self.joysticks.remove(id);
     }
 
+    pub fn poll(&mut self) -> ApplicationResult<()> {
+        self.joysticks.poll()
+    }
+
     pub fn update(&mut self) -> ApplicationResult<()> {
-        self.joysticks.update()
+        self.joysticks.update()?;
+        self.update_calibration()?;
+
+        if let Some(count) = self.joysticks.overflow() {
+            self.toast = Some((self.strings.too_many_inputs(count), Instant::now()));
+        }
+        self.reload_preferences();
+        self.apply_preferences();
+
+        if !self.joysticks.pressed().is_empty() {
+            self.warnings.clear();
+        }
+        self.update_setup_controller();
+
+        let now = Instant::now();
+        self.input_edges(now)?;
+        self.grouping
+            .update(self.joysticks.active(), self.joysticks.pressed(), now);
+
+        let layers = self.active_layers();
+        let mut sprites = match self.grouping.active() {
+            Some(guid) => self
+                .preferences
+                .mapping()
+                .sprites(guid, self.grouping.pressed()),
+            None => Vec::new(),
+        };
+        sprites.retain(|&sprite| self.on_layer(sprite, &layers));
+
+        self.matched.retain(|sprite, _| sprites.contains(sprite));
+
+        for &sprite in &sprites {
+            if let Entry::Vacant(entry) = self.matched.entry(sprite) {
+                entry.insert(now);
+                *self.presses.entry(sprite).or_default() += 1;
+            }
+        }
+
+        let mut shown = Vec::new();
+        let mut partial = Vec::new();
+
+        if !self.setup.enabled() {
+            let mut groups = HashSet::new();
+
+            for sprite in sprites.into_iter().filter(|&sprite| self.held(sprite)) {
+                if let Some(group) = self.sprites.get(&sprite).map(Sprite::group) {
+                    if groups.insert(group) {
+                        shown.push(sprite);
+                    }
+                }
+            }
+
+            if let Some(guid) = self.grouping.active() {
+                let partial_ids = self
+                    .preferences
+                    .mapping()
+                    .partial(guid, self.grouping.pressed());
+
+                for id in partial_ids {
+                    if !self.on_layer(id, &layers) {
+                        continue;
+                    }
+
+                    if let Some(sprite) = self.sprites.get(&id) {
+                        if sprite.partial() && groups.insert(sprite.group()) {
+                            partial.push(id);
+                        }
+                    }
+                }
+            }
+        }
+
+        self.partial = partial;
+
+        if self.preferences.save_due(self.save_interval) {
+            self.save()?;
+        }
+
+        for &sprite in &shown {
+            *self.active_frames.entry(sprite).or_default() += 1;
+        }
+
+        let previous = std::mem::replace(&mut self.shown, shown);
+        let shown = &self.shown;
+        self.appeared.retain(|sprite, _| shown.contains(sprite));
+
+        for &sprite in &previous {
+            if !self.shown.contains(&sprite) {
+                if let Some(sprite) = self.sprites.get(&sprite) {
+                    self.osc.send("sprite", sprite.name(), false);
+                }
+            }
+        }
+
+        for &sprite in &self.shown {
+            if !previous.contains(&sprite) {
+                self.appeared.insert(sprite, now);
+                self.sounds.play(sprite);
+
+                if let Some(sprite) = self.sprites.get(&sprite) {
+                    self.osc.send("sprite", sprite.name(), true);
+                }
+
+                let guid = self.grouping.active();
+                let rumble = self.sprites.get(&sprite).and_then(Sprite::rumble);
+
+                if let (Some(guid), Some(rumble)) = (guid, rumble) {
+                    self.joysticks
+                        .rumble(guid, rumble.low(), rumble.high(), rumble.duration_ms());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Sends OSC messages and records timeline for inputs pressed or released
+    /// since last update. Released inputs belong to previous active controller.
+    fn input_edges(&mut self, now: Instant) -> ApplicationResult<()> {
+        let edges = self
+            .edges
+            .update(self.joysticks.active(), self.joysticks.pressed());
+
+        for (guid, input, pressed) in edges {
+            let input = input.to_string();
+            self.osc.send("input", &input, pressed);
+
+            if let Some(ref mut timeline) = self.timeline {
+                timeline.record(now, &guid, &input, pressed)?;
+            }
+        }
+
+        if let Some(ref mut timeline) = self.timeline {
+            timeline.flush()?;
+        }
+
+        Ok(())
+    }
+
+    /// Records presses and releases of inputs to CSV file.
+    pub fn record_timeline(&mut self, path: &Path) -> ApplicationResult<()> {
+        self.timeline = Some(Timeline::create(path)?);
+
+        Ok(())
+    }
+
+    /// Returns names of layers which modifiers pressed.
+    fn active_layers(&self) -> HashSet<String> {
+        let pressed = self.grouping.pressed();
+
+        self.layers
+            .iter()
+            .filter(|(_, modifier)| pressed.contains(modifier))
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    /// Checks that sprite can be shown with given active layers. Layer sprite
+    /// shown only when its layer active, sprite without layer hidden when
+    /// any layer of its group active.
+    fn on_layer(&self, id: usize, layers: &HashSet<String>) -> bool {
+        match self.sprites.get(&id) {
+            Some(sprite) => match sprite.layer() {
+                Some(layer) => layers.contains(layer),
+                None => self
+                    .group_layers
+                    .get(&sprite.group())
+                    .is_none_or(|group_layers| group_layers.is_disjoint(layers)),
+            },
+            None => false,
+        }
+    }
+
+    /// Checks that sprite input was held at least sprite hold time.
+    fn held(&self, id: usize) -> bool {
+        match (self.sprites.get(&id), self.matched.get(&id)) {
+            (Some(sprite), Some(start)) => start.elapsed() >= sprite.hold(),
+            _ => false,
+        }
+    }
+
+    /// Remembers GUID of first controller used in setup, forgets it when
+    /// setup ends.
+    fn update_setup_controller(&mut self) {
+        if !self.setup.enabled() {
+            self.setup_controller = None;
+        } else if self.setup_controller.is_none() {
+            self.setup_controller = self
+                .joysticks
+                .active()
+                .filter(|guid| self.joysticks.connected(guid))
+                .cloned();
+        }
+    }
+
+    /// Checks that controller used in setup disconnected. Setup waits until
+    /// controller returns and continues from the same sprite.
+    fn setup_paused(&self) -> bool {
+        self.setup.enabled()
+            && self
+                .setup_controller
+                .as_ref()
+                .is_some_and(|guid| !self.joysticks.connected(guid))
     }
 
     pub fn update_setup(&mut self) -> ApplicationResult<()> {
+        if self.setup_paused() {
+            return Ok(());
+        }
+
         if self.setup.enabled() {
             let active = self.joysticks.active();
 
@@ -97,63 +556,411 @@ impl<'a> Visualiser<'a> {
                 let pressed = self.joysticks.pressed();
                 let sprite = self.setup.current();
 
-                self.mapping.push(guid, pressed, sprite);
+                self.preferences.mapping_mut().push(guid, pressed, sprite);
             }
 
             if !self.setup.next_sprite() {
                 if let Some(guid) = active {
                     let empty = HashSet::new();
 
+                    let mut mapping = self.preferences.mapping_mut();
+
                     for &sprite in &self.default {
-                        self.mapping.push(guid, &empty, sprite);
+                        mapping.push(guid, &empty, sprite);
                     }
                 }
 
-                self.mapping.save(&self.preferences)?;
-                self.show_help = false;
+                self.dismiss_help();
+                self.save()?;
+                self.save_limits();
+                self.setup_complete();
             }
         } else {
             self.setup.enable();
+            self.reset_counters();
+        }
+
+        Ok(())
+    }
+
+    fn save(&mut self) -> ApplicationResult<()> {
+        self.preferences.save()?;
+
+        if self.save_text {
+            let sprites = &self.sprites;
+            self.preferences
+                .mapping()
+                .save_text(self.preferences.path().with_extension("txt"), |id| {
+                    sprites.get(&id).map(|sprite| sprite.name().to_string())
+                })?;
+        }
+
+        Ok(())
+    }
+
+    /// Enables reloading of preferences when file changed outside of
+    /// application.
+    pub fn watch_preferences(&mut self) {
+        self.watch = Some(Instant::now());
+    }
+
+    /// Reloads mapping if preferences file changed since last check. Mapping
+    /// kept if file can not be loaded. Reload deferred during setup and while
+    /// mapping has unsaved changes, so bindings are never lost.
+    fn reload_preferences(&mut self) {
+        let checked = match self.watch {
+            Some(ref mut checked) if checked.elapsed() >= WATCH_TIME => checked,
+            _ => return,
+        };
+        *checked = Instant::now();
+
+        if self.setup.enabled() || self.preferences.modified() || !self.preferences.changed() {
+            return;
+        }
+
+        if let Err(error) = self.preferences.reload() {
+            let message = self.strings.preferences_error(&error.to_string());
+            self.toast = Some((message, Instant::now()));
+        }
+    }
+
+    /// Applies pinned limits and selected controller of reloaded mapping,
+    /// mapping may be reloaded by any window.
+    fn apply_preferences(&mut self) {
+        if self.revision == self.preferences.revision() {
+            return;
+        }
+
+        self.revision = self.preferences.revision();
+        let mapping = self.preferences.mapping();
+        self.joysticks.unpin_limits();
+
+        for (guid, limits) in mapping.limits() {
+            for pinned in limits {
+                self.joysticks.pin_limits(guid, pinned);
+            }
+        }
+
+        self.joysticks
+            .set_selected(mapping.selected(self.window).cloned());
+        self.toast = Some((self.strings.preferences_reloaded().into(), Instant::now()));
+    }
+
+    /// Notifies user that mapping saved and runs setup command if configured.
+    fn setup_complete(&mut self) {
+        self.toast = Some((self.strings.mapping_saved().into(), Instant::now()));
+
+        if let Some((program, arguments)) = self
+            .setup_command
+            .as_ref()
+            .and_then(|command| command.split_first())
+        {
+            if let Err(error) = Command::new(program).args(arguments).spawn() {
+                let message = self.strings.setup_command_error(&error.to_string());
+                self.toast = Some((message, Instant::now()));
+            }
+        }
+    }
+
+    /// Saves learned limits and mapping on exit if it was modified since
+    /// last save.
+    pub fn close(&mut self) -> ApplicationResult<()> {
+        self.setup.disable();
+
+        if let Some(ref mut timeline) = self.timeline {
+            timeline.close()?;
         }
 
+        if self.preferences.modified() {
+            self.save()?;
+        }
+
+        self.save_limits();
+
         Ok(())
     }
 
     pub fn cancel_setup(&mut self) {
         self.setup.disable();
+        self.calibration = None;
+    }
+
+    /// Starts calibration of selected, active or first connected controller,
+    /// moves to next calibration step or completes calibration.
+    pub fn calibrate(&mut self) -> ApplicationResult<()> {
+        match self.calibration.take() {
+            None => {
+                let controllers = self.joysticks.controllers();
+                let selected = self.joysticks.selected().map(|device| device_guid(device));
+                let active = self.joysticks.active().map(String::as_str);
+                let guid = [selected, active]
+                    .into_iter()
+                    .flatten()
+                    .find(|&guid| controllers.iter().any(|(_, known)| known == guid))
+                    .or_else(|| controllers.first().map(|(_, guid)| guid.as_str()))
+                    .map(String::from);
+
+                match guid {
+                    Some(guid) => {
+                        self.setup.disable();
+                        self.calibration = Some(Calibration::new(guid));
+                    }
+                    None => {
+                        let message = self.strings.no_controller().into();
+                        self.toast = Some((message, Instant::now()));
+                    }
+                }
+            }
+            Some(mut calibration) if calibration.step == CalibrationStep::Extremes => {
+                calibration.step = CalibrationStep::Center;
+                self.calibration = Some(calibration);
+            }
+            Some(calibration) => {
+                let limits = calibration.limits();
+
+                if limits.is_empty() {
+                    let message = self.strings.calibration_empty().into();
+                    self.toast = Some((message, Instant::now()));
+
+                    return Ok(());
+                }
+
+                for pinned in &limits {
+                    self.joysticks.pin_limits(&calibration.guid, pinned);
+                }
+
+                self.preferences
+                    .mapping_mut()
+                    .set_limits(&calibration.guid, limits);
+                self.save()?;
+                self.toast = Some((self.strings.calibration_saved().into(), Instant::now()));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Records axes of calibrated controller, cancels calibration when
+    /// controller disconnected.
+    fn update_calibration(&mut self) -> ApplicationResult<()> {
+        let calibration = match self.calibration {
+            Some(ref mut calibration) => calibration,
+            None => return Ok(()),
+        };
+
+        match self.joysticks.axes(&calibration.guid)? {
+            Some(axes) => calibration.record(&axes),
+            None => {
+                self.calibration = None;
+                let message = self.strings.calibration_disconnected().into();
+                self.toast = Some((message, Instant::now()));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn toggle_diagnostics(&mut self) {
+        self.show_diagnostics = !self.show_diagnostics;
+    }
+
+    pub fn toggle_fps(&mut self) {
+        self.show_fps = !self.show_fps;
+    }
+
+    pub fn toggle_heatmap(&mut self) {
+        self.show_heatmap = !self.show_heatmap;
     }
 
+    /// Resets press counters and heatmap of all sprites.
+    pub fn reset_counters(&mut self) {
+        self.presses.clear();
+        self.active_frames.clear();
+    }
+
+    /// Resets learned limits, failure to update saved limits is only shown
+    /// as warning.
     pub fn reset_limits(&mut self) {
-        self.joysticks.reset_limits();
+        if let Err(error) = self.joysticks.reset_limits() {
+            self.limits_error(&error.to_string());
+        }
+    }
+
+    /// Saves learned limits, failure is only shown as warning.
+    fn save_limits(&mut self) {
+        if let Err(error) = self.joysticks.save_limits() {
+            self.limits_error(&error.to_string());
+        }
+    }
+
+    fn limits_error(&mut self, error: &str) {
+        let message = self.strings.limits_error(error);
+        self.toast = Some((message, Instant::now()));
+    }
+
+    /// Pins current limits of connected controllers and saves them to
+    /// preferences.
+    pub fn pin_limits(&mut self) -> ApplicationResult<()> {
+        for (_, guid) in self.joysticks.controllers() {
+            let limits = self.joysticks.limits(&guid);
+
+            for pinned in &limits {
+                self.joysticks.pin_limits(&guid, pinned);
+            }
+
+            self.preferences.mapping_mut().set_limits(&guid, limits);
+        }
+
+        self.save()?;
+        self.toast = Some((self.strings.limits_pinned().into(), Instant::now()));
+
+        Ok(())
+    }
+
+    /// Selects next connected controller, after last controller selection is
+    /// cleared and active controller detected automatically again. Selection
+    /// saved to preferences.
+    pub fn cycle_controller(&mut self) {
+        let devices = self.joysticks.devices();
+        let next = match self.joysticks.selected().cloned() {
+            Some(selected) => devices
+                .into_iter()
+                .skip_while(|(_, device)| *device != selected)
+                .find(|(_, device)| *device != selected),
+            None => devices.into_iter().next(),
+        };
+        let message = match next {
+            Some((ref name, _)) => self.strings.controller_selected(name),
+            None => self.strings.selection_cleared().into(),
+        };
+
+        let selected = next.map(|(_, device)| device);
+        self.preferences
+            .mapping_mut()
+            .set_selected(self.window, selected.clone());
+        self.joysticks.set_selected(selected);
+        self.toast = Some((message, Instant::now()));
+    }
+
+    /// Checks that hide input was pressed since last check, window hidden or
+    /// shown on every press.
+    pub fn hide_pressed(&mut self) -> bool {
+        let held = self
+            .hide_input
+            .as_ref()
+            .is_some_and(|input| self.joysticks.pressed().contains(input));
+        let pressed = held && !self.hide_held;
+        self.hide_held = held;
+
+        pressed
+    }
+
+    /// Shows warning until any input pressed. Used for startup warnings,
+    /// which are not visible on console of windowed application.
+    pub fn warn(&mut self, message: String) {
+        self.warnings.push(message);
     }
 
     pub fn key_down(&mut self, key: &str) {
         self.joysticks.key_down(key);
+        self.dismiss_help();
+    }
+
+    /// Hides help message, dismissal saved to preferences.
+    fn dismiss_help(&mut self) {
         self.show_help = false;
+
+        if !self.preferences.mapping().help_dismissed() {
+            self.preferences.mapping_mut().set_help_dismissed(true);
+        }
+    }
+
+    /// Shows help message again, including next runs.
+    pub fn reset_help(&mut self) {
+        self.show_help = true;
+        self.preferences.mapping_mut().set_help_dismissed(false);
     }
 
     pub fn key_up(&mut self, key: &str) {
         self.joysticks.key_up(key);
     }
 
+    /// Draws frame to window. With offscreen target frame drawn in layout
+    /// size and scaled to window at once.
     pub fn draw(&mut self, canvas: &mut WindowCanvas) -> ApplicationResult<()> {
-        canvas.copy(&self.background, None, None)?;
+        match self.target.take() {
+            Some(mut target) => {
+                let mut result = Ok(());
+                let rendered = canvas.with_texture_canvas(&mut target, |target_canvas| {
+                    result = self.draw_scene(target_canvas);
+                });
+
+                if rendered.is_ok() && result.is_ok() {
+                    canvas.set_draw_color(Color::RGB(0, 0, 0));
+                    canvas.clear();
+                    canvas.copy(&target, None, None)?;
+                }
+
+                self.target = Some(target);
+                rendered?;
+
+                result
+            }
+            None => self.draw_scene(canvas),
+        }
+    }
+
+    fn draw_scene(&mut self, canvas: &mut WindowCanvas) -> ApplicationResult<()> {
+        self.frames.push(Instant::now());
+        canvas.set_draw_color(Color::RGB(0, 0, 0));
+        canvas.clear();
+
+        if let Some(ref background) = self.background {
+            canvas.copy(background, None, None)?;
+        }
 
         if self.show_help {
             canvas.set_blend_mode(BlendMode::Blend);
             canvas.set_draw_color(Color::RGBA(0, 0, 0, 192));
             canvas.fill_rect(None)?;
-            self.font.write(
-                canvas,
-                8,
-                8,
-                "Use F1 to start mapping.\nPress any button to hide message.",
-            )?;
+            self.write_block(canvas, &[(0, self.strings.help())])?;
 
-            self.show_help = !self.joysticks.released() || !self.setup.enabled();
+            if self.joysticks.released() && self.setup.enabled() {
+                self.dismiss_help();
+            }
         }
 
-        if self.setup.enabled() {
+        if let Some(ref calibration) = self.calibration {
+            canvas.set_blend_mode(BlendMode::Blend);
+            canvas.set_draw_color(Color::RGBA(0, 0, 0, 192));
+            canvas.fill_rect(None)?;
+
+            let name = self
+                .joysticks
+                .controllers()
+                .into_iter()
+                .find(|(_, guid)| *guid == calibration.guid)
+                .map(|(name, _)| name)
+                .unwrap_or_default();
+            let step = match calibration.step {
+                CalibrationStep::Extremes => self.strings.calibrate_extremes(),
+                CalibrationStep::Center => self.strings.calibrate_center(),
+            };
+            let moved = self
+                .strings
+                .axes_moved(calibration.limits().len(), calibration.ranges.len());
+            let title = self.strings.calibrating(&name);
+
+            self.write_block(
+                canvas,
+                &[
+                    (0, &title),
+                    (40, step),
+                    (112, &moved),
+                    (152, self.strings.calibration_keys()),
+                ],
+            )?;
+        } else if self.setup.enabled() {
             let pressed = self.joysticks.pressed();
             let sprite = self.setup.current();
 
@@ -161,72 +968,508 @@ impl<'a> Visualiser<'a> {
             canvas.set_draw_color(Color::RGBA(0, 0, 0, 192));
             canvas.fill_rect(None)?;
 
+            let mut lines = Vec::new();
+            let mut icons = Vec::new();
+
             if let Some(sprite) = self.sprites.get(&sprite) {
-                canvas.copy(&sprite.texture(), None, None)?;
-
-                self.font.write(
-                    canvas,
-                    8,
-                    8,
-                    &format!("Binding input for {}.", sprite.name()),
-                )?;
-            }
-
-            if !pressed.is_empty() {
-                let mut buttons: Vec<_> = pressed.iter().map(ToString::to_string).collect();
-                buttons.sort();
-
-                self.font.write(
-                    canvas,
-                    8,
-                    48,
-                    &format!("Active keys: {}", buttons.join(", ")),
-                )?;
-                self.font
-                    .write(canvas, 8, 88, "Press: F1 - save, F2 - cancel mapping,")?;
-                self.font
-                    .write(canvas, 8, 120, "       F3 - reset limits.")?;
+                canvas.copy(sprite.texture(), None, sprite.rect())?;
+
+                lines.push((0, self.strings.binding(sprite.name())));
+            }
+
+            if self.setup_paused() {
+                lines.push((40, self.strings.setup_paused().into()));
+                lines.push((80, self.strings.setup_paused_keys().into()));
+            } else if !pressed.is_empty() {
+                let mut inputs: Vec<_> = pressed.iter().collect();
+                inputs.sort_by_key(|input| input.to_string());
+
+                let (keys, columns) = self.input_names(&inputs);
+                let line = self.strings.active_keys(&keys);
+                let prefix = line
+                    .find(&keys)
+                    .map_or(0, |index| line[..index].chars().count());
+
+                for (column, input) in columns {
+                    icons.push((prefix + column, input));
+                }
+
+                lines.push((40, line));
+                lines.push((80, self.strings.save_keys().into()));
             } else {
-                self.font.write(canvas, 8, 48, &format!("No active keys"))?;
-                self.font
-                    .write(canvas, 8, 88, "Press: F1 - skip, F2 - cancel mapping,")?;
-                self.font
-                    .write(canvas, 8, 120, "       F3 - reset limits.")?;
+                lines.push((40, self.strings.no_active_keys().into()));
+                lines.push((80, self.strings.skip_keys().into()));
+            }
+
+            lines.push((112, self.strings.reset_keys().into()));
+
+            let devices = self.joysticks.devices();
+
+            if !devices.is_empty() {
+                lines.push((152, self.strings.controllers().into()));
+            }
+
+            for (index, (name, device)) in devices.iter().enumerate() {
+                let marker = match self.joysticks.active_device() {
+                    Some(active) if active == device => '*',
+                    _ => ' ',
+                };
+
+                lines.push((184 + 32 * index as i32, format!("{} {}", marker, name)));
+            }
+
+            let lines: Vec<_> = lines
+                .iter()
+                .map(|(offset, text)| (*offset, text.as_str()))
+                .collect();
+            let (x, y) = self.write_block(canvas, &lines)?;
+
+            if let Some(ref sheet) = self.icons {
+                let (char_width, char_height) = self.font.size(" ");
+
+                for (column, input) in icons {
+                    if let Some(source) = sheet.source(input) {
+                        let target = Rect::new(
+                            x + (column as u32 * char_width) as i32,
+                            y + 40,
+                            char_height,
+                            char_height,
+                        );
+
+                        canvas.copy(&sheet.texture, source, target)?;
+                    }
+                }
             }
         } else {
-            if let Some(giud) = self.joysticks.active() {
+            let joysticks = &self.joysticks;
+            let visible =
+                |sprite: &Sprite| sprite.guid().is_none_or(|guid| joysticks.connected(guid));
+
+            if self.show_heatmap {
+                let max = self.active_frames.values().copied().max().unwrap_or(0);
+
+                for (id, &frames) in &self.active_frames {
+                    if let Some(sprite) = self.sprites.get_mut(id).filter(|sprite| visible(sprite))
+                    {
+                        copy_tinted(
+                            canvas,
+                            sprite,
+                            frames as f32 / max as f32,
+                            self.color,
+                            self.alpha,
+                            self.premultiplied,
+                        )?;
+                    }
+                }
+            } else if self.grouping.active().is_some() {
                 let mut groups = HashSet::new();
-                let pressed = self.joysticks.pressed();
-                let sprites = self.mapping.sprites(&giud, pressed);
 
-                for sprite in sprites {
-                    if let Some(sprite) = self.sprites.get(&sprite) {
-                        if groups.insert(sprite.group()) {
-                            canvas.copy(&sprite.texture(), None, None)?;
+                for id in &self.shown {
+                    if let Some(sprite) = self.sprites.get_mut(id) {
+                        if !visible(sprite) {
+                            continue;
+                        }
+
+                        let appear = match (self.appeared.get(id), sprite.appear()) {
+                            (Some(start), appear) if !appear.is_zero() => {
+                                (start.elapsed().as_secs_f32() / appear.as_secs_f32()).min(1.0)
+                            }
+                            _ => 1.0,
+                        };
+
+                        groups.insert(sprite.group());
+                        copy_faded(
+                            canvas,
+                            sprite,
+                            appear,
+                            self.color,
+                            self.alpha,
+                            self.premultiplied,
+                        )?;
+                    }
+                }
+
+                for id in &self.partial {
+                    if let Some(sprite) = self.sprites.get_mut(id) {
+                        if !visible(sprite) {
+                            continue;
                         }
+
+                        groups.insert(sprite.group());
+                        copy_faded(
+                            canvas,
+                            sprite,
+                            PARTIAL_ALPHA,
+                            self.color,
+                            self.alpha,
+                            self.premultiplied,
+                        )?;
                     }
                 }
 
-                for sprite in self.default.iter().flat_map(|i| self.sprites.get(i)) {
+                for sprite in self
+                    .default
+                    .iter()
+                    .flat_map(|i| self.sprites.get(i))
+                    .filter(|sprite| visible(sprite))
+                {
                     if groups.insert(sprite.group()) {
-                        canvas.copy(&sprite.texture(), None, None)?;
+                        copy_sprite(canvas, sprite, None, sprite.rect())?;
                     }
                 }
             } else if !self.show_help {
                 let mut groups = HashSet::new();
 
-                for sprite in self.default.iter().flat_map(|i| self.sprites.get(i)) {
+                for sprite in self
+                    .default
+                    .iter()
+                    .flat_map(|i| self.sprites.get(i))
+                    .filter(|sprite| visible(sprite))
+                {
                     if groups.insert(sprite.group()) {
-                        canvas.copy(&sprite.texture(), None, None)?;
+                        copy_sprite(canvas, sprite, None, sprite.rect())?;
+                    }
+                }
+            }
+
+            for sprite in self
+                .bars
+                .iter()
+                .flat_map(|i| self.sprites.get(i))
+                .filter(|sprite| visible(sprite))
+            {
+                if let Some(bar) = sprite.bar() {
+                    let position = joysticks.position(bar.axis()).unwrap_or(0.0);
+                    let query = sprite.texture().query();
+
+                    if let Some((source, destination)) =
+                        bar.rects(query.width, query.height, position)
+                    {
+                        copy_sprite(canvas, sprite, Some(source), Some(destination))?;
+                    }
+                }
+            }
+
+            let players: HashSet<_> = joysticks
+                .controllers()
+                .iter()
+                .filter_map(|(_, guid)| joysticks.player_index(guid))
+                .collect();
+
+            for &(id, player) in &self.players {
+                if let Some(sprite) = self.sprites.get(&id).filter(|sprite| visible(sprite)) {
+                    if players.contains(&player) {
+                        copy_sprite(canvas, sprite, None, sprite.rect())?;
+                    }
+                }
+            }
+
+            for (id, sprite) in &self.sprites {
+                if let Some(counter) = sprite.counter().filter(|_| visible(sprite)) {
+                    let presses = self.presses.get(id).copied().unwrap_or(0);
+
+                    self.font
+                        .write(canvas, counter.x(), counter.y(), &presses.to_string())?;
+                }
+            }
+        }
+
+        if !self.warnings.is_empty() {
+            let lines: Vec<_> = self
+                .warnings
+                .iter()
+                .enumerate()
+                .map(|(index, warning)| (40 * index as i32, warning.as_str()))
+                .collect();
+
+            self.write_block(canvas, &lines)?;
+        } else if let Some((ref text, start)) = self.toast {
+            if start.elapsed() < TOAST_TIME {
+                self.write_block(canvas, &[(0, text)])?;
+            } else {
+                self.toast = None;
+            }
+        }
+
+        if self.show_diagnostics {
+            self.draw_diagnostics(canvas)?;
+        }
+
+        if self.show_fps {
+            let text = format!(
+                "FPS: {}, frame: {:.1} ms",
+                self.frames.fps(),
+                self.frames.frame_time().as_secs_f32() * 1000.0
+            );
+
+            self.write_block_at(canvas, &[(0, &text)], self.text_anchor.mirrored())?;
+        }
+
+        Ok(())
+    }
+
+    /// Joins input names, inputs with icons replaced by blank space. Returns
+    /// text and columns of icons within text.
+    fn input_names<'i>(&self, inputs: &[&'i Input]) -> (String, Vec<(usize, &'i Input)>) {
+        let (char_width, char_height) = self.font.size(" ");
+        let blank = " ".repeat(char_height.div_ceil(char_width.max(1)) as usize);
+        let mut text = String::new();
+        let mut columns = Vec::new();
+
+        for (index, &input) in inputs.iter().enumerate() {
+            if index > 0 {
+                text.push_str(", ");
+            }
+
+            match self.icons {
+                Some(ref sheet) if sheet.cells.contains_key(input) => {
+                    columns.push((text.chars().count(), input));
+                    text.push_str(&blank);
+                }
+                _ => text.push_str(&input.to_string()),
+            }
+        }
+
+        (text, columns)
+    }
+
+    /// Writes text lines at window corner selected by text anchor. Returns
+    /// position of first line.
+    fn write_block(
+        &self,
+        canvas: &mut WindowCanvas,
+        lines: &[(i32, &str)],
+    ) -> ApplicationResult<(i32, i32)> {
+        self.write_block_at(canvas, lines, self.text_anchor)
+    }
+
+    /// Writes text lines at given window corner. Returns position of first
+    /// line.
+    fn write_block_at(
+        &self,
+        canvas: &mut WindowCanvas,
+        lines: &[(i32, &str)],
+        anchor: TextAnchor,
+    ) -> ApplicationResult<(i32, i32)> {
+        let (window_width, window_height) = match canvas.logical_size() {
+            (0, 0) => canvas.output_size()?,
+            size => size,
+        };
+        let (mut width, mut height) = (0, 0);
+
+        for &(offset, text) in lines {
+            let (text_width, text_height) = self.font.size(text);
+            width = width.max(text_width as i32);
+            height = height.max(offset + text_height as i32);
+        }
+
+        let x = match anchor {
+            TextAnchor::TopLeft | TextAnchor::BottomLeft => TEXT_MARGIN,
+            TextAnchor::TopRight | TextAnchor::BottomRight => {
+                window_width as i32 - width - TEXT_MARGIN
+            }
+        };
+        let y = match anchor {
+            TextAnchor::TopLeft | TextAnchor::TopRight => TEXT_MARGIN,
+            TextAnchor::BottomLeft | TextAnchor::BottomRight => {
+                window_height as i32 - height - TEXT_MARGIN
+            }
+        };
+
+        for &(offset, text) in lines {
+            self.font
+                .write(canvas, x.max(0), (y + offset).max(0), text)?;
+        }
+
+        Ok((x.max(0), y.max(0)))
+    }
+
+    /// Shows which sprite mappings match current input and which inputs are
+    /// missing for partially pressed mappings.
+    fn draw_diagnostics(&self, canvas: &mut WindowCanvas) -> ApplicationResult<()> {
+        let mut lines = Vec::new();
+
+        match self.grouping.active() {
+            Some(guid) => {
+                let device = self
+                    .joysticks
+                    .active_device()
+                    .filter(|device| device_guid(device) == guid)
+                    .map_or(guid.as_str(), String::as_str);
+
+                match self.joysticks.player_index(guid) {
+                    Some(player) => {
+                        lines.push(format!("Controller: {}, player index {}", device, player))
+                    }
+                    None => lines.push(format!("Controller: {}", device)),
+                }
+
+                if let Some(buttons) = self.joysticks.buttons(guid) {
+                    let seen: Vec<_> = buttons.seen().iter().map(ToString::to_string).collect();
+
+                    lines.push(format!(
+                        "Buttons: {}, seen: {}",
+                        buttons.count(),
+                        seen.join(", ")
+                    ));
+                }
+
+                let traces = self
+                    .preferences
+                    .mapping()
+                    .trace(guid, self.grouping.pressed());
+
+                match traces {
+                    Some(traces) => {
+                        for trace in traces.iter().filter(|trace| trace.touched()) {
+                            let name = self
+                                .sprites
+                                .get(&trace.sprite())
+                                .map(Sprite::name)
+                                .unwrap_or("Unknown");
+
+                            if trace.matched() {
+                                lines.push(format!("{}: matched", name));
+                            } else {
+                                let mut missing: Vec<_> =
+                                    trace.missing().iter().map(ToString::to_string).collect();
+                                missing.sort();
+
+                                lines.push(format!("{}: missing {}", name, missing.join(", ")));
+                            }
+                        }
                     }
+                    None => lines.push("No mapping for controller".into()),
                 }
             }
+            None => lines.push("No active controller".into()),
         }
 
+        canvas.set_blend_mode(BlendMode::Blend);
+        canvas.set_draw_color(Color::RGBA(0, 0, 0, 192));
+        canvas.fill_rect(None)?;
+        self.write_block(canvas, &[(0, &lines.join("\n"))])?;
+
         Ok(())
     }
 }
 
+/// Counts frames drawn during last second.
+#[derive(Debug)]
+struct FrameCounter {
+    frames: VecDeque<Instant>,
+}
+
+impl FrameCounter {
+    pub fn new() -> Self {
+        Self {
+            frames: VecDeque::new(),
+        }
+    }
+
+    pub fn push(&mut self, now: Instant) {
+        while let Some(&first) = self.frames.front() {
+            if now.duration_since(first) <= FPS_WINDOW {
+                break;
+            }
+
+            self.frames.pop_front();
+        }
+
+        self.frames.push_back(now);
+    }
+
+    pub fn fps(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Returns average time between frames.
+    pub fn frame_time(&self) -> Duration {
+        match (self.frames.front(), self.frames.back()) {
+            (Some(first), Some(last)) if self.frames.len() > 1 => {
+                last.duration_since(*first) / (self.frames.len() - 1) as u32
+            }
+            _ => Duration::ZERO,
+        }
+    }
+}
+
+/// Copies sprite with alpha scaled by opacity, then restores sprite alpha.
+fn copy_faded(
+    canvas: &mut WindowCanvas,
+    sprite: &mut Sprite,
+    opacity: f32,
+    color: u8,
+    alpha: u8,
+    premultiplied: bool,
+) -> ApplicationResult<()> {
+    if opacity >= 1.0 {
+        return copy_sprite(canvas, sprite, None, sprite.rect());
+    }
+
+    let faded = (alpha as f32 * opacity) as u8;
+    blend::set_modulation(sprite.texture_mut(), color, faded, premultiplied);
+    let result = copy_sprite(canvas, sprite, None, sprite.rect());
+    blend::set_modulation(sprite.texture_mut(), color, alpha, premultiplied);
+
+    result
+}
+
+/// Copies sprite tinted by heat from 0.0 (blue) to 1.0 (red).
+fn copy_tinted(
+    canvas: &mut WindowCanvas,
+    sprite: &mut Sprite,
+    heat: f32,
+    color: u8,
+    alpha: u8,
+    premultiplied: bool,
+) -> ApplicationResult<()> {
+    let scale = match premultiplied {
+        true => color as f32 * alpha as f32 / (255.0 * 255.0),
+        false => color as f32 / 255.0,
+    };
+    let red = (255.0 * heat * scale) as u8;
+    let blue = (255.0 * (1.0 - heat) * scale) as u8;
+
+    sprite.texture_mut().set_color_mod(red, 0, blue);
+    let result = copy_sprite(canvas, sprite, None, sprite.rect());
+    blend::set_modulation(sprite.texture_mut(), color, alpha, premultiplied);
+
+    result
+}
+
+/// Copies sprite texture within sprite clip rectangle. Clipping is always
+/// disabled after copy.
+fn copy_sprite(
+    canvas: &mut WindowCanvas,
+    sprite: &Sprite,
+    source: Option<Rect>,
+    destination: Option<Rect>,
+) -> ApplicationResult<()> {
+    canvas.set_clip_rect(sprite.clip());
+    let result = canvas.copy(sprite.texture(), source, destination);
+    canvas.set_clip_rect(None);
+
+    Ok(result?)
+}
+
+fn to_rect(rect: &RectConfig) -> Rect {
+    Rect::new(rect.x(), rect.y(), rect.width(), rect.height())
+}
+
+/// Orders default sprites declared in ascending order, first sprite of group
+/// in resulting order is shown.
+fn order_defaults<F>(default: &mut [usize], selection: DefaultSelection, z: F)
+where
+    F: Fn(usize) -> i32,
+{
+    match selection {
+        DefaultSelection::First => default.sort(),
+        DefaultSelection::Last => default.sort_by_key(|&id| Reverse(id)),
+        DefaultSelection::Z => default.sort_by_key(|&id| (Reverse(z(id)), id)),
+    }
+}
+
 #[derive(Debug)]
 struct SetupOverlay {
     sprites: Vec<usize>,
@@ -271,18 +1514,284 @@ impl SetupOverlay {
     }
 }
 
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum CalibrationStep {
+    Extremes,
+    Center,
+}
+
+/// Calibration of single controller. Axes ranges recorded while user moves
+/// axes to extremes, defaults recorded while axes released.
+#[derive(Debug)]
+struct Calibration {
+    guid: String,
+    step: CalibrationStep,
+    ranges: Vec<(i16, i16)>,
+    defaults: Vec<i16>,
+}
+
+impl Calibration {
+    pub fn new(guid: String) -> Self {
+        Self {
+            guid,
+            step: CalibrationStep::Extremes,
+            ranges: Vec::new(),
+            defaults: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, axes: &[i16]) {
+        for (axis, &value) in axes.iter().enumerate() {
+            match self.ranges.get_mut(axis) {
+                Some((min, max)) => {
+                    *min = value.min(*min);
+                    *max = value.max(*max);
+                }
+                None => self.ranges.push((value, value)),
+            }
+        }
+
+        if self.step == CalibrationStep::Center {
+            self.defaults = axes.to_vec();
+        }
+    }
+
+    /// Returns limits of moved axes, default value is kept within range.
+    /// Axis moved when its travel is at least tenth of full range, so jitter
+    /// of untouched axes is ignored.
+    pub fn limits(&self) -> Vec<PinnedAxis> {
+        self.ranges
+            .iter()
+            .enumerate()
+            .filter(|(_, &(min, max))| max as i32 - min as i32 >= CALIBRATION_TRAVEL)
+            .map(|(axis, &(min, max))| {
+                let default = self.defaults.get(axis).copied().unwrap_or(min);
+
+                PinnedAxis::new(
+                    axis as u32,
+                    default.clamp(min, max) as i32,
+                    min as i32,
+                    max as i32,
+                )
+            })
+            .collect()
+    }
+}
+
+/// Input icons cut from single image.
+struct IconSheet<'a> {
+    texture: Texture<'a>,
+    size: u32,
+    columns: u32,
+    cells: HashMap<Input, u32>,
+}
+
+impl IconSheet<'_> {
+    /// Returns part of image with icon of input.
+    fn source(&self, input: &Input) -> Option<Rect> {
+        self.cells.get(input).map(|&cell| {
+            Rect::new(
+                ((cell % self.columns) * self.size) as i32,
+                ((cell / self.columns) * self.size) as i32,
+                self.size,
+                self.size,
+            )
+        })
+    }
+}
+
+/// Keeps active controller and pressed inputs stable during grouping window,
+/// so inputs pressed almost simultaneously are recognized as single combo.
+#[derive(Debug)]
+struct InputGrouping {
+    window: Duration,
+    changed: Option<Instant>,
+    active: Option<String>,
+    pressed: HashSet<Input>,
+}
+
+impl InputGrouping {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            changed: None,
+            active: None,
+            pressed: HashSet::new(),
+        }
+    }
+
+    pub fn active(&self) -> Option<&String> {
+        self.active.as_ref()
+    }
+
+    pub fn pressed(&self) -> &HashSet<Input> {
+        &self.pressed
+    }
+
+    pub fn update(&mut self, active: Option<&String>, pressed: &HashSet<Input>, now: Instant) {
+        if self.active.as_ref() == active && self.pressed == *pressed {
+            self.changed = None;
+
+            return;
+        }
+
+        let changed = *self.changed.get_or_insert(now);
+
+        if now.duration_since(changed) >= self.window {
+            self.active = active.cloned();
+            self.pressed = pressed.clone();
+            self.changed = None;
+        }
+    }
+}
+
+/// Detects presses and releases of inputs without grouping, so short taps
+/// are never lost.
+#[derive(Debug)]
+struct InputEdges {
+    pressed: HashSet<Input>,
+    active: Option<String>,
+}
+
+impl InputEdges {
+    pub fn new() -> Self {
+        Self {
+            pressed: HashSet::new(),
+            active: None,
+        }
+    }
+
+    /// Returns released and pressed inputs since last update with GUID of
+    /// controller. Released inputs belong to last active controller.
+    pub fn update(
+        &mut self,
+        active: Option<&String>,
+        pressed: &HashSet<Input>,
+    ) -> Vec<(String, Input, bool)> {
+        let released_guid = active.or(self.active.as_ref()).cloned().unwrap_or_default();
+        let pressed_guid = active.cloned().unwrap_or_else(|| released_guid.clone());
+        let mut edges: Vec<_> = self
+            .pressed
+            .difference(pressed)
+            .map(|input| (released_guid.clone(), input.clone(), false))
+            .collect();
+        edges.extend(
+            pressed
+                .difference(&self.pressed)
+                .map(|input| (pressed_guid.clone(), input.clone(), true)),
+        );
+
+        self.pressed.clone_from(pressed);
+
+        if let Some(active) = active {
+            self.active = Some(active.clone());
+        }
+
+        edges
+    }
+}
+
+#[derive(Debug)]
+struct Bar {
+    axis: u32,
+    grow: Grow,
+    rect: Rect,
+}
+
+impl Bar {
+    pub fn axis(&self) -> u32 {
+        self.axis
+    }
+
+    /// Returns source and destination rectangles for bar filled to given position.
+    pub fn rects(&self, width: u32, height: u32, position: f32) -> Option<(Rect, Rect)> {
+        let position = position.clamp(0.0, 1.0);
+        let rect = self.rect;
+        let (source, target) = match self.grow {
+            Grow::Up | Grow::Down => (
+                (height as f32 * position) as u32,
+                (rect.height() as f32 * position) as u32,
+            ),
+            Grow::Left | Grow::Right => (
+                (width as f32 * position) as u32,
+                (rect.width() as f32 * position) as u32,
+            ),
+        };
+
+        if source == 0 || target == 0 {
+            return None;
+        }
+
+        let rects = match self.grow {
+            Grow::Up => (
+                Rect::new(0, (height - source) as i32, width, source),
+                Rect::new(
+                    rect.x(),
+                    rect.bottom() - target as i32,
+                    rect.width(),
+                    target,
+                ),
+            ),
+            Grow::Down => (
+                Rect::new(0, 0, width, source),
+                Rect::new(rect.x(), rect.y(), rect.width(), target),
+            ),
+            Grow::Left => (
+                Rect::new((width - source) as i32, 0, source, height),
+                Rect::new(
+                    rect.right() - target as i32,
+                    rect.y(),
+                    target,
+                    rect.height(),
+                ),
+            ),
+            Grow::Right => (
+                Rect::new(0, 0, source, height),
+                Rect::new(rect.x(), rect.y(), target, rect.height()),
+            ),
+        };
+
+        Some(rects)
+    }
+}
+
 struct Sprite<'a> {
     group: usize,
     name: String,
     texture: Texture<'a>,
+    rect: Option<Rect>,
+    bar: Option<Bar>,
+    hold: Duration,
+    appear: Duration,
+    rumble: Option<Rumble>,
+    partial: bool,
+    guid: Option<String>,
+    layer: Option<String>,
+    counter: Option<Counter>,
+    clip: Option<Rect>,
 }
 
 impl<'a> Sprite<'a> {
-    pub fn new<'b>(group: usize, name: &str, texture: Texture<'b>) -> Sprite<'b> {
+    pub fn new<'b>(
+        sprite: &SpriteConfig,
+        texture: Texture<'b>,
+        bar: Option<Bar>,
+        rect: Option<Rect>,
+    ) -> Sprite<'b> {
         Sprite {
-            group,
-            name: name.into(),
+            group: sprite.group(),
+            name: sprite.name().into(),
             texture,
+            rect,
+            bar,
+            hold: sprite.hold(),
+            appear: sprite.appear(),
+            rumble: sprite.rumble().cloned(),
+            partial: sprite.partial(),
+            guid: sprite.guid().map(Into::into),
+            layer: sprite.layer().map(Into::into),
+            counter: sprite.counter().cloned(),
+            clip: sprite.clip().map(to_rect),
         }
     }
 
@@ -297,4 +1806,147 @@ impl<'a> Sprite<'a> {
     pub fn texture(&self) -> &Texture {
         &self.texture
     }
+
+    pub fn texture_mut(&mut self) -> &mut Texture<'a> {
+        &mut self.texture
+    }
+
+    /// Returns destination rectangle of sprite, `None` means whole window.
+    pub fn rect(&self) -> Option<Rect> {
+        self.rect
+    }
+
+    pub fn bar(&self) -> Option<&Bar> {
+        self.bar.as_ref()
+    }
+
+    pub fn hold(&self) -> Duration {
+        self.hold
+    }
+
+    /// Returns time of sprite fade in after it appears.
+    pub fn appear(&self) -> Duration {
+        self.appear
+    }
+
+    pub fn rumble(&self) -> Option<&Rumble> {
+        self.rumble.as_ref()
+    }
+
+    pub fn partial(&self) -> bool {
+        self.partial
+    }
+
+    pub fn guid(&self) -> Option<&str> {
+        self.guid.as_deref()
+    }
+
+    pub fn layer(&self) -> Option<&str> {
+        self.layer.as_deref()
+    }
+
+    /// Returns rectangle sprite clipped to, `None` means no clipping.
+    pub fn clip(&self) -> Option<Rect> {
+        self.clip
+    }
+
+    /// Returns position of press counter, `None` if counter not shown.
+    pub fn counter(&self) -> Option<&Counter> {
+        self.counter.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ordered(selection: DefaultSelection, z: &[i32]) -> Vec<usize> {
+        let mut default: Vec<_> = (0..z.len()).collect();
+        order_defaults(&mut default, selection, |id| z[id]);
+
+        default
+    }
+
+    #[test]
+    fn tap_within_grouping_window_recorded() {
+        let path =
+            std::env::temp_dir().join(format!("show-gamepad-timeline-{}.csv", std::process::id()));
+        let guid = "0300".to_string();
+        let start = Instant::now();
+        let mut grouping = InputGrouping::new(Duration::from_millis(100));
+        let mut edges = InputEdges::new();
+        let mut timeline = Timeline::create(&path).unwrap();
+        let frames = [(0, HashSet::from([Input::button(0)])), (10, HashSet::new())];
+
+        for (time, pressed) in frames {
+            let now = start + Duration::from_millis(time);
+
+            for (guid, input, pressed) in edges.update(Some(&guid), &pressed) {
+                timeline
+                    .record(now, &guid, &input.to_string(), pressed)
+                    .unwrap();
+            }
+
+            grouping.update(Some(&guid), &pressed, now);
+        }
+
+        timeline.close().unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(grouping.pressed().is_empty());
+        assert_eq!(text.lines().count(), 3);
+        assert!(text
+            .lines()
+            .nth(1)
+            .unwrap()
+            .ends_with(",\"0300\",\"b0\",press"));
+        assert!(text
+            .lines()
+            .nth(2)
+            .unwrap()
+            .ends_with(",\"0300\",\"b0\",release"));
+    }
+
+    #[test]
+    fn calibration_ignores_jittering_axes() {
+        let mut calibration = Calibration::new("0300".into());
+        calibration.record(&[0, -1, -32_768]);
+        calibration.record(&[30_000, 1, -20_000]);
+        calibration.step = CalibrationStep::Center;
+        calibration.record(&[-30_000, 0, -32_768]);
+
+        let limits: Vec<_> = calibration
+            .limits()
+            .iter()
+            .map(|limit| (limit.axis(), limit.default(), limit.min(), limit.max()))
+            .collect();
+
+        assert_eq!(
+            limits,
+            vec![
+                (0, -30_000, -30_000, 30_000),
+                (2, -32_768, -32_768, -20_000)
+            ]
+        );
+    }
+
+    #[test]
+    fn first_default_keeps_declaration_order() {
+        assert_eq!(ordered(DefaultSelection::First, &[0, 2, 1]), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn last_default_reverses_declaration_order() {
+        assert_eq!(ordered(DefaultSelection::Last, &[0, 2, 1]), vec![2, 1, 0]);
+    }
+
+    #[test]
+    fn z_default_prefers_highest_z_then_first_declared() {
+        assert_eq!(ordered(DefaultSelection::Z, &[0, 2, 1]), vec![1, 2, 0]);
+        assert_eq!(
+            ordered(DefaultSelection::Z, &[1, 2, 2, 1]),
+            vec![1, 2, 0, 3]
+        );
+    }
 }